@@ -1,22 +1,34 @@
 use crate::utils::delete_file;
 
-use super::tmpl;
+use super::cache::Cache;
+use super::config::{Config, IconConfig, TranslateConfig};
+use super::formatter::Formatter;
+use super::kotlin;
+use super::model::{Lang, Model};
+use super::openapi;
+use super::rust;
+use super::schema;
+use super::swift;
+use super::typescript;
+use super::tmpl::Templates;
 use super::utils;
-use convert_case::Casing;
+use super::watcher;
 use image::io::Reader as ImageReader;
+use minijinja::context;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::fs::rename;
+use std::path::{Path, PathBuf};
 use std::{collections::HashMap, fs::read_to_string};
 
-const JSON_PATH: &str = "./jsons";
-const OUT_PATH: &str = "./lib/models/";
-
 pub fn build_project(sub_matches: &clap::ArgMatches) {
+    let config = Config::load();
     match sub_matches.subcommand() {
-        Some(("json", _)) => build_json_model(),
-        Some(("translate", sub_matches)) => build_translation(sub_matches),
-        Some(("icon", sub_matches)) => build_icon(sub_matches),
+        Some(("json", sub_matches)) => build_json_model(sub_matches, &config),
+        Some(("openapi", sub_matches)) => build_openapi_model(sub_matches, &config),
+        Some(("translate", sub_matches)) => build_translation(sub_matches, &config),
+        Some(("icon", sub_matches)) => build_icon(sub_matches, &config),
         Some((cmd, _)) => {
             println!("unknow subcommand {}", cmd);
         }
@@ -24,45 +36,596 @@ pub fn build_project(sub_matches: &clap::ArgMatches) {
     }
 }
 
-fn build_json_model() {
-    println!("jsons files:");
-    let files = match utils::read_all_files(JSON_PATH) {
+fn build_json_model(sub_matches: &clap::ArgMatches, config: &Config) {
+    let schema = sub_matches
+        .get_one::<String>("schema")
+        .or(config.models.schema.as_ref());
+    let path = schema.unwrap_or(&config.models.input);
+    let lang = sub_matches
+        .get_one::<String>("lang")
+        .unwrap_or(&config.models.lang);
+    let lang = match Lang::from_name(lang) {
+        Some(lang) => lang,
+        None => {
+            println!(
+                "unknown lang `{}`, use dart, kotlin, rust, swift or typescript",
+                lang
+            );
+            return;
+        }
+    };
+    let style = sub_matches
+        .get_one::<String>("style")
+        .unwrap_or(&config.models.style);
+    let style = match utils::Style::from_name(style) {
+        Some(style) => style,
+        None => {
+            println!(
+                "unknown style `{}`, use plain, json_serializable or freezed",
+                style
+            );
+            return;
+        }
+    };
+    if style != utils::Style::Plain && config.naming.file_suffix.ends_with(".g.dart") {
+        println!(
+            "warning: build_runner writes `.g.dart` files too, set `naming.file_suffix` to `.dart`"
+        );
+    }
+    let templates = Templates::load(&config.root);
+    let target = Target {
+        out: sub_matches
+            .get_one::<String>("out")
+            .unwrap_or(&config.models.output),
+        lang,
+        package: sub_matches
+            .get_one::<String>("package")
+            .or(config.models.package.as_ref())
+            .map(String::as_str),
+        zod: sub_matches.get_flag("zod") || config.models.zod,
+        style,
+        options: model_options(sub_matches, &config.models.with),
+        templates: &templates,
+        formatter: match lang {
+            Lang::Dart => formatter(sub_matches, config),
+            _ => Formatter::None,
+        },
+    };
+
+    let mut cache = Cache::load(&config.root, sub_matches.get_flag("force"));
+    let mut outputs = HashMap::new();
+    generate_json_models(path, schema.is_some(), target, None, &mut cache, &mut outputs);
+
+    if sub_matches.get_flag("watch") {
+        watcher::watch(std::slice::from_ref(path), |changed| {
+            generate_json_models(
+                path,
+                schema.is_some(),
+                target,
+                Some(changed),
+                &mut cache,
+                &mut outputs,
+            )
+        });
+    }
+}
+
+/// Where and how the json models are generated.
+#[derive(Clone, Copy)]
+struct Target<'a> {
+    out: &'a str,
+    lang: Lang,
+    package: Option<&'a str>,
+    zod: bool,
+    style: utils::Style,
+    options: utils::ModelOptions,
+    templates: &'a Templates,
+    formatter: Formatter,
+}
+
+/// Generate the models of every file in `path`, or only of the `changed` ones.
+/// `outputs` keeps the files written for each json sample, so the output of a
+/// removed sample can be deleted. A schema can be inlined into any other
+/// schema, so schemas are always generated all together.
+fn generate_json_models(
+    path: &str,
+    is_schema: bool,
+    target: Target,
+    changed: Option<&[PathBuf]>,
+    cache: &mut Cache,
+    outputs: &mut HashMap<PathBuf, Vec<String>>,
+) {
+    println!("{} files:", if is_schema { "schema" } else { "jsons" });
+    let files = match utils::read_all_files(path) {
         Ok(Some(files)) => files,
         _ => return,
     };
 
-    for file in files {
-        let dart_info = utils::parse_to_dart(&file);
-        let name = &file.name.to_case(convert_case::Case::Pascal);
-        let class_name = format!("{}Model", name);
-        let field_list = dart_info.fields;
-        let imports = utils::generate_imports(dart_info.imports);
-        let fields = utils::generate_fields(&field_list);
-        let ctor = utils::generate_ctor(&field_list);
-        let from_json = utils::generate_from_json(&field_list);
-        let to_json = utils::generate_to_json(&field_list);
-
-        let source = tmpl::DART_TMPL
-            .replace("{imports}", &imports)
-            .replace("{className}", class_name.as_str())
-            .replace("{fields}", &fields)
-            .replace("{ctor}", &ctor)
-            .replace("{fromJson}", &from_json)
-            .replace("{toJson}", &to_json);
-
-        if !utils::check_and_create(OUT_PATH) {
-            continue;
+    if !utils::check_and_create(target.out) {
+        return;
+    }
+
+    let mut variants = Vec::new();
+    let (files, models): (Vec<utils::FileInfo>, Vec<utils::DartInfo>) = if is_schema {
+        match schema::parse_schemas(&files) {
+            Ok(models) => (files, models),
+            Err(err) => return println!("{}", err),
         }
+    } else {
+        let parsed: Vec<Result<utils::DartInfo, String>> =
+            files.par_iter().map(utils::parse_to_dart).collect();
+        let errors: Vec<&String> = parsed.iter().filter_map(|e| e.as_ref().err()).collect();
+        if !errors.is_empty() {
+            for err in errors {
+                println!("{}", err);
+            }
+            return;
+        }
+        let models: Vec<utils::DartInfo> = parsed.into_iter().flatten().collect();
+        // a sample read as the variant of a union is only written in its file
+        variants = models.iter().flat_map(variant_sources).collect();
+        files
+            .into_iter()
+            .zip(models)
+            .filter(|(file, _)| !variants.contains(&source_path(file)))
+            .unzip()
+    };
+
+    let dirs = model_dirs(&models);
+    let neutral: Vec<Model> = match target.lang {
+        Lang::Dart => Vec::new(),
+        _ => models.iter().map(Model::from_dart).collect(),
+    };
+    let exports = match target.lang {
+        Lang::TypeScript => typescript::exports(&neutral),
+        _ => HashMap::new(),
+    };
+    let modules = match target.lang {
+        Lang::Rust => rust::exports(&neutral),
+        _ => rust::Exports::default(),
+    };
+    let rendered: Result<Vec<_>, String> = models
+        .par_iter()
+        .enumerate()
+        .filter(|(idx, dart_info)| {
+            is_schema
+                || changed.is_none_or(|changed| {
+                    changed.contains(&source_path(&files[*idx]))
+                        || variant_sources(dart_info)
+                            .iter()
+                            .any(|e| changed.contains(e))
+                })
+        })
+        .map(|(idx, dart_info)| {
+            let mut sources = Vec::new();
+            match target.lang {
+                Lang::Dart => {
+                    let (options, style) = (target.options, target.style);
+                    let templates = target.templates;
+                    render_dart_model(dart_info, options, style, templates, &dirs, &mut sources)?
+                }
+                Lang::Kotlin => sources.push(kotlin::render(&neutral[idx], target.package)),
+                Lang::Rust => sources.push(rust::render(&neutral[idx], &dirs, &modules)),
+                Lang::Swift => sources.push(swift::render(&neutral[idx])),
+                Lang::TypeScript => {
+                    typescript::render(&neutral[idx], &dirs, &exports, target.zod, &mut sources)
+                }
+            }
+            Ok((idx, sources))
+        })
+        .collect();
+    let rendered: Vec<(usize, Vec<(String, String)>)> = match rendered {
+        Ok(rendered) => rendered,
+        Err(err) => return println!("{}", err),
+    };
 
-        let dart_file = format!("{}{}.g.dart", OUT_PATH, &file.name);
-        utils::write_with_format(&dart_file, &source);
+    let mut formats = Vec::new();
+    for (idx, sources) in &rendered {
+        if !is_schema {
+            let names = sources.iter().map(|(name, _)| name.clone()).collect();
+            outputs.insert(source_path(&files[*idx]), names);
+        }
+        formats.extend(write_dart_files(target.out, sources, target.formatter, cache));
+    }
+    if target.lang == Lang::Swift && neutral.iter().any(swift::needs_json_value) {
+        write_dart_files(target.out, &[swift::json_value_file()], target.formatter, cache);
+    }
+    if target.lang == Lang::Rust {
+        write_dart_files(target.out, &rust::modules(&neutral), target.formatter, cache);
+    }
+    if target.lang == Lang::Dart {
+        if model_files(&models).iter().any(|(e, _)| e == BARREL) {
+            println!(
+                "warning: a model is written to {}, the barrel is skipped",
+                BARREL
+            );
+        } else {
+            let barrel = utils::generate_barrel(&barrel_files(&models));
+            let sources = [(String::from(BARREL), barrel)];
+            let written = write_dart_files(target.out, &sources, target.formatter, cache);
+            formats.extend(written);
+        }
+    }
+    let removed = changed.unwrap_or_default().iter().filter(|e| !e.exists());
+    for path in removed.chain(&variants) {
+        for file in outputs.remove(path).unwrap_or_default() {
+            println!("\t- remove {}", file);
+            let file = out_file(target.out, &file);
+            let _ = std::fs::remove_file(&file);
+            cache.remove(&file);
+        }
     }
 
+    format_dart_files(&formats, target.formatter, cache);
+    cache.save();
     println!("build finish");
 }
 
-const TRANS_OUT: &str = "./lib/i18n/";
-const TRANS_PATH: &str = "./translation/";
+fn source_path(file: &utils::FileInfo) -> PathBuf {
+    std::fs::canonicalize(&file.path).unwrap_or_else(|_| PathBuf::from(&file.path))
+}
+
+/// The sample files variants of the model were read from.
+fn variant_sources(info: &utils::DartInfo) -> Vec<PathBuf> {
+    info.union
+        .iter()
+        .flat_map(|e| &e.variants)
+        .filter_map(|e| e.source.as_ref().map(PathBuf::from))
+        .collect()
+}
+
+fn build_openapi_model(sub_matches: &clap::ArgMatches, config: &Config) {
+    let spec = match sub_matches
+        .get_one::<String>("spec")
+        .or(config.openapi.spec.as_ref())
+    {
+        Some(spec) => spec,
+        None => {
+            println!("no openapi spec provided");
+            return;
+        }
+    };
+    println!("build models from: {}", spec);
+
+    let content = match read_to_string(spec) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("read spec failed: {}", e);
+            return;
+        }
+    };
+
+    let out = sub_matches
+        .get_one::<String>("out")
+        .unwrap_or(&config.openapi.output);
+    if !utils::check_and_create(out) {
+        return;
+    }
+
+    let options = model_options(sub_matches, &config.openapi.with);
+    let formatter = formatter(sub_matches, config);
+    let templates = Templates::load(&config.root);
+    let mut cache = Cache::load(&config.root, sub_matches.get_flag("force"));
+    let models = openapi::parse_spec(&content);
+    let dirs = model_dirs(&models);
+    let rendered: Result<Vec<Vec<(String, String)>>, String> = models
+        .par_iter()
+        .map(|dart_info| {
+            let mut sources = Vec::new();
+            let style = utils::Style::Plain;
+            render_dart_model(dart_info, options, style, &templates, &dirs, &mut sources)?;
+            Ok(sources)
+        })
+        .collect();
+    let mut sources: Vec<(String, String)> = match rendered {
+        Ok(rendered) => rendered.into_iter().flatten().collect(),
+        Err(err) => return println!("{}", err),
+    };
+
+    sources.push((
+        String::from(BARREL),
+        utils::generate_barrel(&barrel_files(&models)),
+    ));
+    let written = write_dart_files(out, &sources, formatter, &mut cache);
+    format_dart_files(&written, formatter, &mut cache);
+    cache.save();
+
+    println!("build finish");
+}
+
+/// `--with` replaces the list from the config.
+fn model_options(sub_matches: &clap::ArgMatches, with: &Option<String>) -> utils::ModelOptions {
+    sub_matches
+        .get_one::<String>("with")
+        .or(with.as_ref())
+        .map(|e| utils::ModelOptions::from_list(e))
+        .unwrap_or_default()
+}
+
+fn out_file(out: &str, name: &str) -> String {
+    Path::new(out).join(name).display().to_string()
+}
+
+/// The file exporting every dart model, in the output dir.
+const BARREL: &str = "models.dart";
+
+/// The files of the models and their children with the dart names they
+/// declare, sorted.
+fn model_files(models: &[utils::DartInfo]) -> Vec<(String, Vec<String>)> {
+    fn declared(info: &utils::DartInfo, names: &mut Vec<String>) {
+        if !info.fields.is_empty() || info.enums.is_empty() || info.union.is_some() {
+            names.push(utils::model_name(&info.name));
+        }
+        names.extend(info.enums.iter().map(|e| e.name.clone()));
+        if let Some(union) = &info.union {
+            for variant in &union.variants {
+                declared(&variant.info, names);
+            }
+            names.extend(union.fallback.iter().map(|e| utils::model_name(e)));
+        }
+    }
+
+    fn collect(info: &utils::DartInfo, files: &mut Vec<(String, Vec<String>)>) {
+        let mut names = Vec::new();
+        declared(info, &mut names);
+        let file = utils::join_dir(&info.dir, &utils::model_file(&info.name));
+        files.push((file, names));
+        for child in &info.children {
+            collect(child, files);
+        }
+    }
+
+    let mut files = Vec::new();
+    for info in models {
+        collect(info, &mut files);
+    }
+    files.sort();
+    files.dedup_by(|a, b| a.0 == b.0);
+    files
+}
+
+/// The model files the barrel exports, a file declaring a name an earlier
+/// one already does is left out as the export would be ambiguous.
+fn barrel_files(models: &[utils::DartInfo]) -> Vec<String> {
+    let mut declared: HashMap<String, String> = HashMap::new();
+    let mut result = Vec::new();
+    for (file, names) in model_files(models) {
+        if let Some((name, other)) = names.iter().find_map(|e| declared.get(e).map(|o| (e, o))) {
+            println!(
+                "warning: `{}` of {} is declared in {} too, {} leaves it out",
+                name, file, other, BARREL
+            );
+            continue;
+        }
+        for name in names {
+            declared.insert(name, file.clone());
+        }
+        result.push(file);
+    }
+    result
+}
+
+/// Every dir a model name is written to, imports are resolved against it.
+fn model_dirs(models: &[utils::DartInfo]) -> HashMap<String, Vec<String>> {
+    fn collect(info: &utils::DartInfo, dirs: &mut HashMap<String, Vec<String>>) {
+        let found = dirs.entry(info.name.clone()).or_default();
+        if !found.contains(&info.dir) {
+            found.push(info.dir.clone());
+        }
+        for child in &info.children {
+            collect(child, dirs);
+        }
+    }
+
+    let mut dirs = HashMap::new();
+    for info in models {
+        collect(info, &mut dirs);
+    }
+    dirs
+}
+
+/// Render the model and its children, collecting each file path relative to
+/// `out` with its source. Children inherit the options of the model they are nested in.
+fn render_dart_model(
+    dart_info: &utils::DartInfo,
+    options: utils::ModelOptions,
+    style: utils::Style,
+    templates: &Templates,
+    dirs: &HashMap<String, Vec<String>>,
+    sources: &mut Vec<(String, String)>,
+) -> Result<(), String> {
+    let options = options.merge(dart_info.options);
+    for child in &dart_info.children {
+        render_dart_model(child, options, style, templates, dirs, sources)?;
+    }
+
+    let class_name = utils::model_name(&dart_info.name);
+    let imports = utils::generate_imports(&dart_info.dir, &dart_info.imports, dirs);
+    let file_name = utils::join_dir(&dart_info.dir, &utils::model_file(&dart_info.name));
+
+    let source = if let Some(union) = &dart_info.union {
+        if style != utils::Style::Plain {
+            println!(
+                "\t  warning: the union `{}` is generated with its own json code",
+                dart_info.name
+            );
+        }
+        let mut variants = String::new();
+        for variant in &union.variants {
+            let class = Class {
+                name: "variant",
+                imports: "",
+                part: "",
+                base_name: &class_name,
+            };
+            variants.push_str(&generate_class(templates, class, &variant.info, options)?);
+            let enums = utils::generate_enums(&variant.info.enums, false, templates)?;
+            variants.push_str(&enums);
+        }
+        if let Some(fallback) = &union.fallback {
+            let context = context! {
+                class_name => utils::model_name(fallback),
+                base_name => &class_name,
+                key => &union.key,
+            };
+            variants.push_str(&templates.render("unknown", context)?);
+        }
+
+        let context = context! {
+            model => dart_info,
+            class_name,
+            imports,
+            from_json => utils::generate_union_from_json(&class_name, union),
+            variants,
+        };
+        templates.render("sealed", context)?
+            + &utils::generate_enums(&dart_info.enums, false, templates)?
+    } else if dart_info.fields.is_empty() && !dart_info.enums.is_empty() {
+        let import = match style {
+            utils::Style::Plain => "",
+            utils::Style::JsonSerializable => {
+                "\nimport 'package:json_annotation/json_annotation.dart';\n"
+            }
+            utils::Style::Freezed => {
+                "\nimport 'package:freezed_annotation/freezed_annotation.dart';\n"
+            }
+        };
+        let header = templates.render("header", context! {})?;
+        format!("{}library;\n{}", header, import)
+            + &utils::generate_enums(&dart_info.enums, style != utils::Style::Plain, templates)?
+    } else {
+        let (name, part) = match style {
+            utils::Style::Plain => ("model", ""),
+            utils::Style::JsonSerializable => ("json_serializable", file_name.as_str()),
+            utils::Style::Freezed => ("freezed", file_name.as_str()),
+        };
+        let part = part.rsplit('/').next().unwrap_or(part);
+        let class = Class {
+            name,
+            imports: &imports,
+            part: part.trim_end_matches(".dart"),
+            base_name: "",
+        };
+        generate_class(templates, class, dart_info, options)?
+            + &utils::generate_enums(&dart_info.enums, style != utils::Style::Plain, templates)?
+    };
+
+    sources.push((file_name, source));
+    Ok(())
+}
+
+/// `--formatter` replaces the one from the config.
+fn formatter(sub_matches: &clap::ArgMatches, config: &Config) -> Formatter {
+    let name = sub_matches
+        .get_one::<String>("formatter")
+        .or(config.formatter.as_ref());
+    Formatter::from_name(name.map(String::as_str))
+}
+
+/// Write the rendered files that changed since the last build, returning
+/// the written paths. The formatter is part of the cached source, so
+/// switching it rewrites every file.
+fn write_dart_files(
+    out: &str,
+    sources: &[(String, String)],
+    formatter: Formatter,
+    cache: &mut Cache,
+) -> Vec<String> {
+    let mut written = Vec::new();
+    for (name, source) in sources {
+        let dart_file = out_file(out, name);
+        if cache.is_fresh(&dart_file, &format!("{}\n{}", formatter.name(), source)) {
+            continue;
+        }
+        let dir = Path::new(&dart_file).parent().unwrap_or(Path::new(out));
+        if utils::check_and_create(&dir.display().to_string())
+            && utils::write_file(&dart_file, &formatter.format(source))
+        {
+            written.push(dart_file);
+        }
+    }
+    written
+}
+
+/// Run `dart format` over the written files when it is the formatter, a
+/// file it fails on is written again by the next build.
+fn format_dart_files(files: &[String], formatter: Formatter, cache: &mut Cache) {
+    if formatter == Formatter::Dart {
+        for file in utils::format_files(files) {
+            cache.remove(&file);
+        }
+    }
+}
+
+/// The template a class is rendered with and what it needs beyond the model.
+struct Class<'a> {
+    name: &'a str,
+    imports: &'a str,
+    /// Names the files build_runner generates for an annotated template.
+    part: &'a str,
+    /// The sealed class of a variant.
+    base_name: &'a str,
+}
+
+fn generate_class(
+    templates: &Templates,
+    class: Class,
+    dart_info: &utils::DartInfo,
+    options: utils::ModelOptions,
+) -> Result<String, String> {
+    let class_name = utils::model_name(&dart_info.name);
+    let field_list = &dart_info.fields;
+    let (declarations, params, converters) = if class.part.is_empty() {
+        (
+            utils::generate_fields(field_list),
+            String::new(),
+            String::new(),
+        )
+    } else {
+        (
+            utils::generate_annotated_fields(field_list, &dart_info.enums),
+            utils::generate_freezed_params(field_list, &dart_info.enums),
+            utils::generate_converters(field_list),
+        )
+    };
+    let ctor = utils::generate_ctor(field_list);
+    let from_json = utils::generate_from_json(field_list);
+    let to_json = utils::generate_to_json(field_list);
+
+    let options = options.merge(dart_info.options);
+    let mut extras = String::new();
+    if options.copy_with == Some(true) {
+        extras.push_str(&utils::generate_copy_with(&class_name, field_list));
+    }
+    if options.equality == Some(true) {
+        extras.push_str(&utils::generate_equality(&class_name, field_list));
+    }
+    if options.to_string == Some(true) {
+        extras.push_str(&utils::generate_to_string(&class_name, field_list));
+    }
+
+    let fields: Vec<_> = field_list
+        .iter()
+        .map(|e| context! { dart_type => utils::dart_type(e), ..minijinja::Value::from_serialize(e) })
+        .collect();
+    let context = context! {
+        model => dart_info,
+        fields,
+        options,
+        class_name,
+        imports => class.imports,
+        declarations,
+        ctor,
+        from_json,
+        to_json,
+        extras,
+        params,
+        converters,
+        part => class.part,
+        base_name => class.base_name,
+    };
+    templates.render(class.name, context)
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransItem {
@@ -72,47 +635,88 @@ pub struct TransItem {
     pub content: HashMap<String, String>,
 }
 
-fn build_translation(sub_matches: &clap::ArgMatches) {
-    let def_from = "json".to_string();
-    let def_to = "dart".to_string();
-    let from = sub_matches.get_one::<String>("from").unwrap_or(&def_from);
-    let to = sub_matches.get_one::<String>("to").unwrap_or(&def_to);
+fn build_translation(sub_matches: &clap::ArgMatches, config: &Config) {
+    let formatter = formatter(sub_matches, config);
+    let config = &config.translate;
+    let from = sub_matches
+        .get_one::<String>("from")
+        .unwrap_or(&config.from);
+    let to = sub_matches.get_one::<String>("to").unwrap_or(&config.to);
     println!("build translation from: {} to: {}", from, to);
 
-    if !utils::check_and_create(TRANS_OUT) {
+    if !utils::check_and_create(&config.output) {
         return;
     }
 
-    match from.as_str() {
-        "json" => build_from_json(to),
-        "csv" => build_from_csv(to),
+    let generate = || match from.as_str() {
+        "json" => build_from_json(to, config, formatter),
+        "csv" => build_from_csv(to, config, formatter),
         _ => println!("Invalid source format"),
+    };
+    generate();
+
+    if sub_matches.get_flag("watch") {
+        // only watch the sources, `--to csv` writes into the input dir
+        let mut sources = vec![format!("{}/translations.json", config.input)];
+        if from == "csv" {
+            sources.push(format!("{}/translations.csv", config.input));
+        }
+        watcher::watch(&sources, |_| generate());
     }
 }
 
-fn build_from_json(to: &str) {
-    let json_str = read_to_string(format!("{}/translations.json", TRANS_PATH)).unwrap();
-    let trans_items: Vec<TransItem> = serde_json::from_str(&json_str).unwrap();
+/// The items of `translations.json`.
+fn read_translations(config: &TranslateConfig) -> Option<Vec<TransItem>> {
+    let path = format!("{}/translations.json", config.input);
+    let items = read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|e| serde_json::from_str(&e).map_err(|e| e.to_string()));
+    match items {
+        Ok(items) => Some(items),
+        Err(err) => {
+            println!("read {} failed: {}", path, err);
+            None
+        }
+    }
+}
+
+fn build_from_json(to: &str, config: &TranslateConfig, formatter: Formatter) {
+    let trans_items = match read_translations(config) {
+        Some(items) => items,
+        None => return,
+    };
     let ikeys = utils::generate_ikeys(&trans_items);
 
     match to {
         "dart" => {
-            utils::write_with_format(&format!("{}/const_key.dart", TRANS_OUT), &ikeys);
-            for lang in &["zh", "en"] {
+            let mut written = vec![format!("{}/const_key.dart", config.output)];
+            utils::write_file(&written[0], &formatter.format(&ikeys));
+            for lang in &config.languages {
+                let lang_path = format!("{}/i18n_{}.dart", config.output, lang);
                 let lang_source = utils::generate_translation(&trans_items, lang);
-                utils::write_with_format(
-                    &format!("{}/i18n_{}.dart", TRANS_OUT, lang),
-                    &lang_source,
-                );
+                if utils::write_file(&lang_path, &formatter.format(&lang_source)) {
+                    written.push(lang_path);
+                }
+            }
+            write_translation_index(&config.output, &config.languages, formatter, &mut written);
+            if formatter == Formatter::Dart {
+                utils::format_files(&written);
             }
         }
         "csv" => {
-            let csv_path = format!("{}/translations.csv", TRANS_PATH);
-            let new_csv_path = format!("{}/temp.csv", TRANS_PATH);
-            let mut reader = csv::Reader::from_path(&csv_path).unwrap();
-            let mut writer = csv::Writer::from_path(&new_csv_path).unwrap();
+            let csv_path = format!("{}/translations.csv", config.input);
+            let new_csv_path = format!("{}/temp.csv", config.input);
+            let reader = csv::Reader::from_path(&csv_path);
+            let writer = csv::Writer::from_path(&new_csv_path);
+            let (mut reader, mut writer) = match (reader, writer) {
+                (Ok(reader), Ok(writer)) => (reader, writer),
+                (Err(e), _) | (_, Err(e)) => return println!("open csv failed: {}", e),
+            };
 
-            let header = reader.headers().unwrap();
+            let header = match reader.headers() {
+                Ok(header) => header,
+                Err(e) => return println!("read {} failed: {}", csv_path, e),
+            };
             let len = header.len();
             let _ = writer.write_record(header);
 
@@ -127,19 +731,26 @@ fn build_from_json(to: &str) {
     }
 }
 
-fn build_from_csv(to: &str) {
-    let csv_path = format!("{}/translations.csv", TRANS_PATH);
-    let mut reader = csv::Reader::from_path(&csv_path).unwrap();
-    let header = reader.headers().unwrap().clone();
+fn build_from_csv(to: &str, config: &TranslateConfig, formatter: Formatter) {
+    let csv_path = format!("{}/translations.csv", config.input);
+    let header = csv::Reader::from_path(&csv_path)
+        .and_then(|mut reader| Ok((reader.headers()?.clone(), reader)));
+    let (header, mut reader) = match header {
+        Ok(header) => header,
+        Err(e) => return println!("read {} failed: {}", csv_path, e),
+    };
     let langs: Vec<String> = header.iter().map(|e| e.to_string()).collect();
     let records: Vec<csv::StringRecord> = reader.records().flat_map(|opt| opt).collect();
 
     match to {
         "dart" => {
-            let json_str = read_to_string(format!("{}/translations.json", TRANS_PATH)).unwrap();
-            let trans_items: Vec<TransItem> = serde_json::from_str(&json_str).unwrap();
+            let trans_items = match read_translations(config) {
+                Some(items) => items,
+                None => return,
+            };
             let ikeys = utils::generate_ikeys(&trans_items);
-            utils::write_with_format(&format!("{}/const_key.dart", TRANS_OUT), &ikeys);
+            let mut written = vec![format!("{}/const_key.dart", config.output)];
+            utils::write_file(&written[0], &formatter.format(&ikeys));
 
             for lang in &langs[1..] {
                 let mut new_items = trans_items.clone();
@@ -159,9 +770,15 @@ fn build_from_csv(to: &str) {
                     }
                 }
 
-                let lang_path = format!("{}/i18n_{}.dart", TRANS_OUT, lang);
+                let lang_path = format!("{}/i18n_{}.dart", config.output, lang);
                 let lang_source = utils::generate_translation(&new_items, &lang);
-                utils::write_with_format(&lang_path, &lang_source);
+                if utils::write_file(&lang_path, &formatter.format(&lang_source)) {
+                    written.push(lang_path);
+                }
+            }
+            write_translation_index(&config.output, &langs[1..], formatter, &mut written);
+            if formatter == Formatter::Dart {
+                utils::format_files(&written);
             }
         }
         "json" => { /* build json */ }
@@ -169,6 +786,41 @@ fn build_from_csv(to: &str) {
     }
 }
 
+/// Write the `index.dart` of the translations in `languages`, removing the
+/// translations generated for any other language.
+fn write_translation_index(
+    output: &str,
+    languages: &[String],
+    formatter: Formatter,
+    written: &mut Vec<String>,
+) {
+    let index = format!("{}/index.dart", output);
+    let source = utils::generate_translation_index(languages);
+    if utils::write_file(&index, &formatter.format(&source)) {
+        written.push(index);
+    }
+
+    let files: Vec<String> = languages
+        .iter()
+        .map(|e| format!("i18n_{}.dart", e))
+        .collect();
+    let entries = match std::fs::read_dir(output) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stale = name.starts_with("i18n_")
+            && name.ends_with(".dart")
+            && !files.contains(&name)
+            && read_to_string(entry.path()).is_ok_and(|e| e.starts_with("part of 'index.dart'"));
+        if stale {
+            println!("\t- remove {}", entry.path().display());
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
 const ANDROID_CONFIGS: &'static str = r#"{
     "mipmap-hdpi": 72,
     "mipmap-mdpi": 48,
@@ -176,8 +828,6 @@ const ANDROID_CONFIGS: &'static str = r#"{
     "mipmap-xxhdpi": 144,
     "mipmap-xxxhdpi": 192
 }"#;
-const ANDROID_OUT: &str = "android/app/src/main/res";
-const IOS_OUT: &str = "ios/Runner/Assets.xcassets/AppIcon.appiconset";
 
 #[derive(Serialize, Deserialize, Debug)]
 struct IOSConfig {
@@ -190,18 +840,31 @@ struct IOSSizeConfig {
     scale: String,
 }
 
-fn build_icon(sub_matches: &clap::ArgMatches) {
-    let file = "./data/icon.png".to_string();
-    let out = ".".to_string();
-    let platforms = "android,ios".to_string();
-    let file = sub_matches.get_one::<String>("file").unwrap_or(&file);
-    let out = sub_matches.get_one::<String>("out").unwrap_or(&out);
+fn build_icon(sub_matches: &clap::ArgMatches, config: &Config) {
+    let config = &config.icon;
+    let file = sub_matches
+        .get_one::<String>("file")
+        .unwrap_or(&config.file);
+    let out = sub_matches.get_one::<String>("out").unwrap_or(&config.out);
     let platforms = sub_matches
         .get_one::<String>("platforms")
-        .unwrap_or(&platforms);
+        .unwrap_or(&config.platforms);
 
-    let img = ImageReader::open(file).expect("Image open error");
-    let img = img.decode().unwrap();
+    generate_icons(file, out, platforms, config);
+
+    if sub_matches.get_flag("watch") {
+        watcher::watch(std::slice::from_ref(file), |_| {
+            generate_icons(file, out, platforms, config)
+        });
+    }
+}
+
+fn generate_icons(file: &str, out: &str, platforms: &str, config: &IconConfig) {
+    let img = match ImageReader::open(file).map(|e| e.decode()) {
+        Ok(Ok(img)) => img,
+        Ok(Err(e)) => return println!("Image decode error: {}", e),
+        Err(e) => return println!("Image open error: {}", e),
+    };
 
     if platforms.contains("android") {
         println!("Begin generate icons for Android");
@@ -211,7 +874,10 @@ fn build_icon(sub_matches: &clap::ArgMatches) {
             let size = item.1.as_number().unwrap().as_u64().unwrap() as u32;
             let img = img.clone();
             let img = img.resize(size, size, image::imageops::FilterType::CatmullRom);
-            let res = img.save(format!("{}/{}/{}/ic_launcher.png", out, ANDROID_OUT, item.0));
+            let res = img.save(format!(
+                "{}/{}/{}/ic_launcher.png",
+                out, config.android, item.0
+            ));
             match res {
                 Ok(_) => {
                     pb.inc(1);
@@ -225,17 +891,29 @@ fn build_icon(sub_matches: &clap::ArgMatches) {
     }
     if platforms.contains("ios") {
         println!("Begin generate icons for IOS");
-        let json_str = read_to_string(format!("{}/Contents.json", IOS_OUT)).unwrap();
-        let configs: IOSConfig = serde_json::from_str(&json_str).unwrap();
+        let path = format!("{}/{}/Contents.json", out, config.ios);
+        let configs = read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|e| serde_json::from_str::<IOSConfig>(&e).map_err(|e| e.to_string()));
+        let configs = match configs {
+            Ok(configs) => configs,
+            Err(err) => return println!("read {} failed: {}", path, err),
+        };
         let pb = utils::show_progress(configs.images.len() as u64);
         for item in configs.images {
             let size: Vec<&str> = item.size.split("x").collect();
-            let size: f32 = size.first().unwrap().parse().unwrap();
-            let scale: f32 = item.scale.replace("x", "").parse().unwrap();
-            let size = (size * scale) as u32;
+            let size: Option<f32> = size.first().and_then(|e| e.parse().ok());
+            let scale: Option<f32> = item.scale.replace("x", "").parse().ok();
+            let size = match size.zip(scale) {
+                Some((size, scale)) => (size * scale) as u32,
+                None => {
+                    println!("invalid size {} of {}", item.size, item.filename);
+                    continue;
+                }
+            };
             let img = img.clone();
             let img = img.resize(size, size, image::imageops::FilterType::CatmullRom);
-            let res = img.save(format!("{}/{}/{}.png", out, IOS_OUT, item.filename));
+            let res = img.save(format!("{}/{}/{}.png", out, config.ios, item.filename));
             match res {
                 Ok(_) => {
                     pb.inc(1);
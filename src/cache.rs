@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CACHE_FILE: &str = ".dart_tool/xtools/cache.json";
+
+/// Hashes of the generated outputs from the last build. The hash covers the
+/// generator version and the unformatted source, so it changes with the
+/// inputs, the options and anything else the output depends on.
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    version: String,
+    outputs: HashMap<String, String>,
+}
+
+pub struct Cache {
+    path: PathBuf,
+    file: CacheFile,
+    force: bool,
+}
+
+impl Cache {
+    /// Load the cache of the project in `root`, `force` makes every output stale.
+    pub fn load(root: &Path, force: bool) -> Cache {
+        let path = root.join(CACHE_FILE);
+        let file = fs::read_to_string(&path)
+            .ok()
+            .and_then(|e| serde_json::from_str::<CacheFile>(&e).ok())
+            .filter(|e| e.version == env!("CARGO_PKG_VERSION"))
+            .unwrap_or_default();
+        Cache { path, file, force }
+    }
+
+    /// Whether `output` already holds `source`, otherwise the new hash is recorded.
+    pub fn is_fresh(&mut self, output: &str, source: &str) -> bool {
+        let hash = format!("{:x}", Sha256::digest(source.as_bytes()));
+        let fresh = !self.force
+            && Path::new(output).exists()
+            && self.file.outputs.get(output) == Some(&hash);
+        if !fresh {
+            self.file.outputs.insert(output.to_string(), hash);
+        }
+        fresh
+    }
+
+    pub fn remove(&mut self, output: &str) {
+        self.file.outputs.remove(output);
+    }
+
+    pub fn save(&mut self) {
+        self.file.version = env!("CARGO_PKG_VERSION").to_string();
+        let content = serde_json::to_string_pretty(&self.file).unwrap();
+        let saved = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&self.path, content));
+        if let Err(e) = saved {
+            println!("write cache failed: {}", e);
+        }
+    }
+}
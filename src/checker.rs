@@ -0,0 +1,463 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+
+use crate::config::Config;
+use crate::model::{Field, Type};
+use crate::utils::{self, DartInfo, EnumInfo};
+
+pub fn check_project(sub_matches: &clap::ArgMatches) {
+    let config = Config::load();
+    match sub_matches.subcommand() {
+        Some(("json", sub_matches)) => check_json(sub_matches, &config),
+        Some((cmd, _)) => {
+            println!("unknow subcommand {}", cmd);
+        }
+        None => {}
+    }
+}
+
+/// Check captured payloads against the model parsed from the json samples,
+/// exiting with 1 when any of them differs.
+fn check_json(sub_matches: &clap::ArgMatches, config: &Config) {
+    let name = sub_matches.get_one::<String>("model").unwrap();
+    let payloads: Vec<&String> = sub_matches.get_many::<String>("payload").unwrap().collect();
+
+    println!("jsons files:");
+    let files = match utils::read_all_files(&config.models.input) {
+        Ok(Some(files)) => files,
+        _ => {
+            println!("read {} failed", config.models.input);
+            std::process::exit(1);
+        }
+    };
+    let models: Vec<DartInfo> = files
+        .iter()
+        .map(utils::parse_to_dart)
+        .collect::<Result<_, String>>()
+        .unwrap_or_else(|err| {
+            println!("{}", err);
+            std::process::exit(1);
+        });
+    let checker = Checker::new(&models);
+
+    let found = checker.find(name);
+    let model = match found.as_slice() {
+        [model] => *model,
+        [] => {
+            println!("no model `{}` in {}", name, config.models.input);
+            std::process::exit(1);
+        }
+        _ => {
+            let mut paths: Vec<String> = found
+                .iter()
+                .map(|e| utils::join_dir(&e.dir, &e.name))
+                .collect();
+            paths.sort();
+            println!("`{}` is ambiguous, use one of: {}", name, paths.join(", "));
+            std::process::exit(1);
+        }
+    };
+
+    let class_name = utils::model_name(&model.name);
+    println!("check payloads against {}:", class_name);
+    let (mut total, mut failed) = (0, 0);
+    for file in payload_files(&payloads) {
+        total += 1;
+        let problems = match fs::read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|e| serde_json::from_str::<Value>(&e).map_err(|e| e.to_string()))
+        {
+            Ok(Value::Array(items)) => {
+                let mut problems = Vec::new();
+                for (idx, item) in items.iter().enumerate() {
+                    checker.check_model(model, item, &format!("$[{}]", idx), &mut problems);
+                }
+                problems
+            }
+            Ok(value) => {
+                let mut problems = Vec::new();
+                checker.check_model(model, &value, "$", &mut problems);
+                problems
+            }
+            Err(err) => vec![format!("read failed: {}", err)],
+        };
+
+        if problems.is_empty() {
+            println!("\t- {}: ok", file.display());
+        } else {
+            failed += 1;
+            println!("\t- {}: {} differences", file.display(), problems.len());
+            for problem in problems {
+                println!("\t  {}", problem);
+            }
+        }
+    }
+
+    if total == 0 {
+        println!("no payloads found");
+        std::process::exit(1);
+    }
+    if failed > 0 {
+        println!(
+            "{} of {} payloads differ from {}",
+            failed, total, class_name
+        );
+        std::process::exit(1);
+    }
+    println!("{} payloads match {}", total, class_name);
+}
+
+/// The payload files, a dir stands for the json files in it.
+fn payload_files(payloads: &[&String]) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+    for payload in payloads {
+        let path = PathBuf::from(payload);
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(&path)
+                .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+                .unwrap_or_default();
+            files.retain(|e| e.extension().is_some_and(|ext| ext == "json"));
+            files.sort();
+            result.extend(files);
+        } else {
+            result.push(path);
+        }
+    }
+    result
+}
+
+/// Every model and enum of the samples with the dir of their file, children
+/// and variants of unions included.
+struct Checker<'a> {
+    classes: Vec<(&'a str, String, &'a DartInfo)>,
+    enums: Vec<(&'a str, String, &'a EnumInfo)>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(models: &'a [DartInfo]) -> Checker<'a> {
+        fn collect<'a>(info: &'a DartInfo, checker: &mut Checker<'a>) {
+            let class_name = utils::model_name(&info.name);
+            checker.classes.push((&info.dir, class_name, info));
+            for item in &info.enums {
+                checker.enums.push((&info.dir, item.name.clone(), item));
+            }
+            for child in &info.children {
+                collect(child, checker);
+            }
+            for variant in info.union.iter().flat_map(|e| &e.variants) {
+                collect(&variant.info, checker);
+            }
+        }
+
+        let mut checker = Checker {
+            classes: Vec::new(),
+            enums: Vec::new(),
+        };
+        for info in models {
+            collect(info, &mut checker);
+        }
+        checker
+    }
+
+    /// The models `name` stands for, by file name or by path below the input dir.
+    fn find(&self, name: &str) -> Vec<&'a DartInfo> {
+        self.classes
+            .iter()
+            .map(|e| e.2)
+            .filter(|e| e.name == name || utils::join_dir(&e.dir, &e.name) == name)
+            .collect()
+    }
+
+    /// Collect where `value` at `path` differs from what the model's
+    /// `fromJson` reads: missing required keys, nulls it can't hold, values
+    /// of another type and keys it doesn't know.
+    fn check_model(&self, info: &DartInfo, value: &Value, path: &str, problems: &mut Vec<String>) {
+        let map = match value {
+            Value::Object(map) => map,
+            _ => {
+                problems.push(format!(
+                    "{}: expected an object of {}, found {}",
+                    path,
+                    utils::model_name(&info.name),
+                    kind(value)
+                ));
+                return;
+            }
+        };
+        if info.union.is_some() {
+            self.check_union(info, map, value, path, problems);
+            return;
+        }
+
+        let fields: Vec<Field> = info.fields.iter().map(Field::from_dart).collect();
+        for field in &fields {
+            let at = format!("{}.{}", path, field.key);
+            match map.get(&field.key) {
+                None if field.required => problems.push(format!("{}: missing required key", at)),
+                None => {}
+                Some(Value::Null) => {
+                    let allowed = field.nullable
+                        || field.default.is_some()
+                        || matches!(field.ty, Type::List(..));
+                    if !allowed {
+                        problems.push(format!("{}: null in a non-nullable field", at));
+                    }
+                }
+                Some(value) => self.check_type(&field.ty, value, &info.dir, &at, problems),
+            }
+        }
+        for key in map.keys() {
+            if !fields.iter().any(|e| e.key == *key) {
+                problems.push(format!("{}.{}: unexpected key", path, key));
+            }
+        }
+    }
+
+    /// Check against the variant the discriminator names, or the first one
+    /// whose required keys are all there.
+    fn check_union(
+        &self,
+        info: &DartInfo,
+        map: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        problems: &mut Vec<String>,
+    ) {
+        let union = info.union.as_ref().unwrap();
+        let class_name = utils::model_name(&info.name);
+        let variant = match &union.key {
+            Some(key) => {
+                let tag = map.get(key).unwrap_or(&Value::Null);
+                let variant = union.variants.iter().find(|e| e.tag.as_ref() == Some(tag));
+                if variant.is_none() {
+                    problems.push(format!(
+                        "{}.{}: no variant of {} is tagged {}",
+                        path, key, class_name, tag
+                    ));
+                }
+                variant
+            }
+            None => {
+                let variant = union.variants.iter().find(|variant| {
+                    let fields = &variant.info.fields;
+                    fields
+                        .iter()
+                        .filter(|e| e.required)
+                        .all(|e| map.contains_key(&e.key))
+                });
+                if variant.is_none() {
+                    problems.push(format!("{}: matches no variant of {}", path, class_name));
+                }
+                variant
+            }
+        };
+        if let Some(variant) = variant {
+            self.check_model(&variant.info, value, path, problems);
+        }
+    }
+
+    /// Models and enums are looked up in `dir`, the one of the referring model.
+    fn check_type(
+        &self,
+        ty: &Type,
+        value: &Value,
+        dir: &str,
+        path: &str,
+        problems: &mut Vec<String>,
+    ) {
+        let matched = match ty {
+            Type::String | Type::Uri => value.is_string(),
+            Type::DateTime { millis: false } => value.as_str().is_some_and(utils::is_iso_date_time),
+            Type::Int | Type::Duration | Type::DateTime { millis: true } => {
+                value.is_i64() || value.is_u64()
+            }
+            // an int decodes as a dart int, failing `as double`
+            Type::Double => value.is_f64(),
+            Type::Num => value.is_number(),
+            Type::Bool => value.is_boolean(),
+            Type::Dynamic | Type::Named(_) => true,
+            Type::Enum(name) => {
+                let members = lookup(&self.enums, dir, name).map(|e| e.values.as_slice());
+                if value.is_array() || value.is_object() {
+                    false
+                } else {
+                    if members.is_some_and(|members| !members.contains(value)) {
+                        problems.push(format!("{}: {} is not a member of {}", path, value, name));
+                    }
+                    true
+                }
+            }
+            Type::Model(name) => {
+                if let Some(info) = lookup(&self.classes, dir, name) {
+                    self.check_model(info, value, path, problems);
+                    return;
+                }
+                value.is_object()
+            }
+            Type::List(element, nullable) => match value {
+                Value::Array(items) => {
+                    for (idx, item) in items.iter().enumerate() {
+                        let at = format!("{}[{}]", path, idx);
+                        if item.is_null() {
+                            if !nullable && **element != Type::Dynamic {
+                                problems.push(format!("{}: null in a non-nullable list", at));
+                            }
+                        } else {
+                            self.check_type(element, item, dir, &at, problems);
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            },
+        };
+        if !matched {
+            problems.push(format!(
+                "{}: expected {}, found {}",
+                path,
+                expected(ty),
+                kind(value)
+            ));
+        }
+    }
+}
+
+/// The one named `name` in `dir`, else the only one of that name in any dir.
+fn lookup<'a, T>(items: &[(&str, String, &'a T)], dir: &str, name: &str) -> Option<&'a T> {
+    let named: Vec<&(&str, String, &'a T)> = items.iter().filter(|e| e.1 == name).collect();
+    match named.iter().find(|e| e.0 == dir) {
+        Some(found) => Some(found.2),
+        None if named.len() == 1 => Some(named[0].2),
+        None => None,
+    }
+}
+
+fn expected(ty: &Type) -> String {
+    match ty {
+        Type::String | Type::Uri => String::from("a string"),
+        Type::DateTime { millis: false } => String::from("a date string"),
+        Type::Int => String::from("an int"),
+        Type::Duration | Type::DateTime { millis: true } => String::from("int milliseconds"),
+        Type::Double => String::from("a double"),
+        Type::Num => String::from("a number"),
+        Type::Bool => String::from("a bool"),
+        Type::Dynamic | Type::Named(_) => String::from("any value"),
+        Type::Enum(name) => format!("a member of {}", name),
+        Type::Model(name) => format!("an object of {}", name),
+        Type::List(..) => String::from("a list"),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) if value.is_f64() => "a double",
+        Value::Number(_) => "an int",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The models of `(dir, name, sample)` files.
+    fn parse(files: &[(&str, &str, Value)]) -> Vec<DartInfo> {
+        files
+            .iter()
+            .map(|(dir, name, sample)| {
+                let file = utils::FileInfo {
+                    name: name.to_string(),
+                    path: format!("jsons/{}.json", utils::join_dir(dir, name)),
+                    dir: dir.to_string(),
+                    content: sample.to_string(),
+                };
+                utils::parse_to_dart(&file).unwrap()
+            })
+            .collect()
+    }
+
+    fn problems(checker: &Checker, info: &DartInfo, payload: Value) -> Vec<String> {
+        let mut problems = Vec::new();
+        checker.check_model(info, &payload, "$", &mut problems);
+        problems
+    }
+
+    /// The problems of `payload` against the model of `sample`.
+    fn check(sample: Value, payload: Value) -> Vec<String> {
+        let models = parse(&[("", "user", sample)]);
+        problems(&Checker::new(&models), &models[0], payload)
+    }
+
+    #[test]
+    fn reports_missing_required_key() {
+        let problems = check(json!({"r@id": 1, "name": "x"}), json!({"name": "y"}));
+        assert_eq!(problems, vec!["$.id: missing required key"]);
+    }
+
+    #[test]
+    fn reports_type_mismatch() {
+        let problems = check(
+            json!({"r@id": 1, "tags": ["a"]}),
+            json!({"id": "1", "tags": ["b", 2]}),
+        );
+        assert_eq!(
+            problems,
+            vec![
+                "$.id: expected an int, found a string",
+                "$.tags[1]: expected a string, found an int",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unexpected_key() {
+        let problems = check(json!({"r@id": 1}), json!({"id": 2, "extra": true}));
+        assert_eq!(problems, vec!["$.extra: unexpected key"]);
+    }
+
+    #[test]
+    fn reports_null_in_non_nullable_field() {
+        let problems = check(
+            json!({"r@id": 1, "d@count": 3, "name": "x"}),
+            json!({"id": null, "count": null, "name": null}),
+        );
+        assert_eq!(problems, vec!["$.id: null in a non-nullable field"]);
+    }
+
+    #[test]
+    fn tells_same_named_models_apart_by_dir() {
+        let models = parse(&[
+            ("a", "item", json!({"r@id": 1})),
+            ("a", "order", json!({"r@items": "[]item"})),
+            ("b", "item", json!({"r@name": "x"})),
+        ]);
+        let checker = Checker::new(&models);
+
+        assert_eq!(checker.find("item").len(), 2);
+        let found = checker.find("b/item");
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            problems(&checker, found[0], json!({"name": "y"})),
+            Vec::<String>::new()
+        );
+
+        let order = checker.find("a/order")[0];
+        let ok = problems(&checker, order, json!({"items": [{"id": 2}]}));
+        assert_eq!(ok, Vec::<String>::new());
+        let wrong = problems(&checker, order, json!({"items": [{"name": "y"}]}));
+        assert_eq!(
+            wrong,
+            vec![
+                "$.items[0].id: missing required key",
+                "$.items[0].name: unexpected key",
+            ]
+        );
+    }
+}
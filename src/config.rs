@@ -0,0 +1,230 @@
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const CONFIG_FILE: &str = "xtools.yaml";
+const PUBSPEC_FILE: &str = "pubspec.yaml";
+const PUBSPEC_SECTION: &str = "xtools";
+
+static NAMING: OnceLock<Naming> = OnceLock::new();
+
+/// Project settings from `xtools.yaml` or the `xtools:` section of `pubspec.yaml`,
+/// every missing key keeps the built-in default.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub models: ModelsConfig,
+    pub openapi: OpenapiConfig,
+    pub translate: TranslateConfig,
+    pub icon: IconConfig,
+    pub naming: Naming,
+    /// builtin, dart or none, `dart format` when the SDK is on PATH by default.
+    pub formatter: Option<String>,
+    /// The dir the config was found in, or the current dir.
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ModelsConfig {
+    pub input: String,
+    pub schema: Option<String>,
+    pub output: String,
+    pub with: Option<String>,
+    /// dart, kotlin, rust, swift or typescript.
+    pub lang: String,
+    /// The package of the kotlin models.
+    pub package: Option<String>,
+    /// Add zod schemas to the typescript models.
+    pub zod: bool,
+    /// plain, json_serializable or freezed, how dart models read json.
+    pub style: String,
+}
+
+impl Default for ModelsConfig {
+    fn default() -> Self {
+        ModelsConfig {
+            input: String::from("./jsons"),
+            schema: None,
+            output: String::from("./lib/models/"),
+            with: None,
+            lang: String::from("dart"),
+            package: None,
+            zod: false,
+            style: String::from("plain"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct OpenapiConfig {
+    pub spec: Option<String>,
+    pub output: String,
+    pub with: Option<String>,
+}
+
+impl Default for OpenapiConfig {
+    fn default() -> Self {
+        OpenapiConfig {
+            spec: None,
+            output: String::from("./lib/models/"),
+            with: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct TranslateConfig {
+    pub input: String,
+    pub output: String,
+    pub from: String,
+    pub to: String,
+    pub languages: Vec<String>,
+}
+
+impl Default for TranslateConfig {
+    fn default() -> Self {
+        TranslateConfig {
+            input: String::from("./translation/"),
+            output: String::from("./lib/i18n/"),
+            from: String::from("json"),
+            to: String::from("dart"),
+            languages: vec![String::from("zh"), String::from("en")],
+        }
+    }
+}
+
+/// `android` and `ios` are relative to `out`, the flutter project.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct IconConfig {
+    pub file: String,
+    pub out: String,
+    pub platforms: String,
+    pub android: String,
+    pub ios: String,
+}
+
+impl Default for IconConfig {
+    fn default() -> Self {
+        IconConfig {
+            file: String::from("./data/icon.png"),
+            out: String::from("."),
+            platforms: String::from("android,ios"),
+            android: String::from("android/app/src/main/res"),
+            ios: String::from("ios/Runner/Assets.xcassets/AppIcon.appiconset"),
+        }
+    }
+}
+
+/// How generated model classes and files are named.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Naming {
+    pub class_prefix: String,
+    pub class_suffix: String,
+    pub file_suffix: String,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Naming {
+            class_prefix: String::new(),
+            class_suffix: String::from("Model"),
+            file_suffix: String::from(".g.dart"),
+        }
+    }
+}
+
+impl Config {
+    /// Find the config walking up from the current dir, relative paths in it
+    /// are resolved against the dir it was found in.
+    pub fn load() -> Config {
+        let mut config = match find_config() {
+            Some((file, config)) => {
+                println!("config: {}", file.display());
+                let root = file.parent().unwrap_or(Path::new("."));
+                let mut config = config.resolve(root);
+                config.root = root.to_path_buf();
+                config
+            }
+            None => Config::default(),
+        };
+        if config.root.as_os_str().is_empty() {
+            config.root = PathBuf::from(".");
+        }
+        let _ = NAMING.set(config.naming.clone());
+        config
+    }
+
+    fn resolve(mut self, root: &Path) -> Config {
+        let join = |path: &mut String| {
+            if Path::new(path.as_str()).is_relative() {
+                *path = root.join(path.trim_start_matches("./")).display().to_string();
+            }
+        };
+        join(&mut self.models.input);
+        join(&mut self.models.output);
+        if let Some(schema) = &mut self.models.schema {
+            join(schema);
+        }
+        join(&mut self.openapi.output);
+        if let Some(spec) = &mut self.openapi.spec {
+            join(spec);
+        }
+        join(&mut self.translate.input);
+        join(&mut self.translate.output);
+        join(&mut self.icon.file);
+        join(&mut self.icon.out);
+        self
+    }
+}
+
+/// The naming rules of the loaded config, or the defaults.
+pub fn naming() -> &'static Naming {
+    NAMING.get_or_init(Naming::default)
+}
+
+/// The nearest `xtools.yaml`, or `pubspec.yaml` with an `xtools:` section.
+fn find_config() -> Option<(PathBuf, Config)> {
+    let cwd = std::env::current_dir().ok()?;
+    for dir in cwd.ancestors() {
+        let file = dir.join(CONFIG_FILE);
+        if file.is_file() {
+            let config = parse_config(read_yaml(&file), &file);
+            return Some((file, config));
+        }
+
+        let file = dir.join(PUBSPEC_FILE);
+        if file.is_file() {
+            if let Some(section) = read_yaml(&file).get(PUBSPEC_SECTION) {
+                let config = parse_config(section.clone(), &file);
+                return Some((file, config));
+            }
+        }
+    }
+    None
+}
+
+fn read_yaml(file: &Path) -> Value {
+    let content =
+        read_to_string(file).unwrap_or_else(|e| panic!("read {} failed: {}", file.display(), e));
+    if content.trim().is_empty() {
+        return Value::Null;
+    }
+    serde_yaml::from_str(&content).unwrap_or_else(|e| panic!("invalid {}: {}", file.display(), e))
+}
+
+/// An empty file or section is the default config.
+fn parse_config(value: Value, file: &Path) -> Config {
+    if value.is_null() {
+        return Config::default();
+    }
+    serde_yaml::from_value(value)
+        .unwrap_or_else(|e| panic!("invalid config in {}: {}", file.display(), e))
+}
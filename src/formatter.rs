@@ -0,0 +1,1232 @@
+use std::env;
+use std::path::Path;
+
+/// How generated dart files are formatted.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Formatter {
+    Builtin,
+    Dart,
+    None,
+}
+
+impl Formatter {
+    /// `builtin`, `dart` or `none`, without a name `dart format` is used when
+    /// the dart SDK is on PATH and the built-in formatter otherwise.
+    pub fn from_name(name: Option<&str>) -> Formatter {
+        match name {
+            Some("builtin") => Formatter::Builtin,
+            Some("dart") => Formatter::Dart,
+            Some("none") => Formatter::None,
+            Some(other) => {
+                println!("unknown formatter `{}`, use builtin, dart or none", other);
+                Formatter::detect()
+            }
+            None => Formatter::detect(),
+        }
+    }
+
+    fn detect() -> Formatter {
+        let found = env::var_os("PATH").is_some_and(|paths| {
+            env::split_paths(&paths).any(|dir| {
+                ["dart", "dart.exe", "dart.bat"]
+                    .iter()
+                    .any(|name| Path::new(&dir).join(name).is_file())
+            })
+        });
+        if found {
+            Formatter::Dart
+        } else {
+            Formatter::Builtin
+        }
+    }
+
+    /// The source as it is written, `dart format` runs on the written files.
+    pub fn format(&self, source: &str) -> String {
+        match self {
+            Formatter::Builtin => format_dart(source),
+            _ => source.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Formatter::Builtin => "builtin",
+            Formatter::Dart => "dart",
+            Formatter::None => "none",
+        }
+    }
+}
+
+/// The page width of `dart format`.
+const WIDTH: usize = 80;
+
+/// Format the dart xtools generates the way `dart format` does: classes,
+/// enums, constructors, methods, switches and map or list literals. Source
+/// it can't handle is returned unchanged.
+pub fn format_dart(source: &str) -> String {
+    let tokens = match tokenize(source) {
+        Some(tokens) => tokens,
+        None => return source.to_string(),
+    };
+    let nodes = match build_tree(tokens.clone()) {
+        Some(nodes) => nodes,
+        None => return source.to_string(),
+    };
+
+    let mut lines = Vec::new();
+    format_block(&nodes, 0, Body::TopLevel, &mut lines);
+    let result = lines.join("\n") + "\n";
+
+    // never lose or reorder code, whatever the layout rules got wrong
+    match tokenize(&result) {
+        Some(formatted) if same_tokens(&tokens, &formatted) => result,
+        _ => {
+            println!("builtin formatter left a file unformatted");
+            source.to_string()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    Word,
+    Number,
+    Str,
+    Comment,
+    Punct,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: Kind,
+    text: String,
+    /// Whether whitespace came before the token in the source.
+    space: bool,
+    /// The newlines between the token and the one before it.
+    newlines: usize,
+}
+
+const PUNCTS: &[&str] = &[
+    "...?", "??=", "...", "~/=", "<<=", "?.", "??", "=>", "==", "!=", "<=", ">=", "&&", "||", "++",
+    "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "~/", "<<", "..",
+];
+
+const KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "await", "throw", "yield", "in", "is", "as",
+    "case", "else", "on", "const", "new", "async",
+];
+
+fn tokenize(source: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+    loop {
+        let mut newlines = 0;
+        let start = i;
+        while i < chars.len() && chars[i].is_whitespace() {
+            if chars[i] == '\n' {
+                newlines += 1;
+            }
+            i += 1;
+        }
+        if i >= chars.len() {
+            return Some(tokens);
+        }
+        let space = i > start || tokens.is_empty();
+        let rest = &chars[i..];
+
+        let (kind, end) = if rest.starts_with(&['/', '/']) {
+            let len = rest.iter().position(|c| *c == '\n').unwrap_or(rest.len());
+            (Kind::Comment, i + len)
+        } else if rest.starts_with(&['/', '*']) {
+            let len = (2..rest.len()).find(|j| rest[*j - 1] == '*' && rest[*j] == '/')?;
+            (Kind::Comment, i + len + 1)
+        } else if is_quote(rest) {
+            (Kind::Str, scan_string(&chars, i)?)
+        } else if rest[0].is_alphabetic() || rest[0] == '_' || rest[0] == '$' {
+            let len = rest
+                .iter()
+                .position(|c| !(c.is_alphanumeric() || *c == '_' || *c == '$'))
+                .unwrap_or(rest.len());
+            (Kind::Word, i + len)
+        } else if rest[0].is_ascii_digit()
+            || (rest[0] == '.' && rest.get(1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            let len = rest
+                .iter()
+                .position(|c| !(c.is_ascii_alphanumeric() || *c == '.'))
+                .unwrap_or(rest.len());
+            (Kind::Number, i + len)
+        } else {
+            let len = PUNCTS
+                .iter()
+                .find(|p| rest.iter().take(p.len()).copied().eq(p.chars()))
+                .map_or(1, |p| p.len());
+            (Kind::Punct, i + len)
+        };
+
+        let mut text: String = chars[i..end].iter().collect();
+        let mut end = end;
+        if kind == Kind::Comment {
+            text = text.trim_end().to_string();
+        }
+
+        let last = tokens.last();
+        let operand = match last {
+            None => true,
+            Some(last) => {
+                (last.kind == Kind::Punct && last.text != ")" && last.text != "]")
+                    || (last.kind == Kind::Word && KEYWORDS.contains(&last.text.as_str()))
+            }
+        };
+        // `Map<String, dynamic>` and `<String, String>{` are kept as one word
+        if chars.get(end) == Some(&'<') && kind == Kind::Word {
+            if let Some((close, args)) = scan_type_args(&chars, end) {
+                text.push_str(&args);
+                end = close;
+            }
+        } else if text == "<" && operand {
+            if let Some((close, args)) = scan_type_args(&chars, i) {
+                let next = chars[close..].iter().find(|c| !c.is_whitespace());
+                if next == Some(&'{') || next == Some(&'[') {
+                    tokens.push(Token {
+                        kind: Kind::Word,
+                        text: args,
+                        space,
+                        newlines,
+                    });
+                    i = close;
+                    continue;
+                }
+            }
+        }
+
+        if let Some(last) = tokens.last_mut() {
+            if last.text == "operator" && kind == Kind::Punct && text != "(" {
+                last.text = format!("operator {}", text);
+                i = end;
+                continue;
+            }
+        }
+        tokens.push(Token {
+            kind,
+            text,
+            space,
+            newlines,
+        });
+        i = end;
+    }
+}
+
+fn is_quote(rest: &[char]) -> bool {
+    matches!(rest, ['\'' | '"', ..] | ['r', '\'' | '"', ..])
+}
+
+/// The end of the string literal starting at `start`, with its interpolations.
+fn scan_string(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start;
+    let raw = chars[i] == 'r';
+    if raw {
+        i += 1;
+    }
+    let quote = chars[i];
+    let triple = chars.len() >= i + 3 && chars[i + 1] == quote && chars[i + 2] == quote;
+    i += if triple { 3 } else { 1 };
+
+    while i < chars.len() {
+        let c = chars[i];
+        if triple
+            && c == quote
+            && chars.len() >= i + 3
+            && chars[i + 1] == quote
+            && chars[i + 2] == quote
+        {
+            return Some(i + 3);
+        } else if !triple && c == quote {
+            return Some(i + 1);
+        } else if !triple && c == '\n' {
+            return None;
+        } else if !raw && c == '\\' {
+            i += 2;
+        } else if !raw && c == '$' && chars.get(i + 1) == Some(&'{') {
+            i = scan_interpolation(chars, i + 2)?;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn scan_interpolation(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut i = start;
+    while i < chars.len() {
+        if is_quote(&chars[i..]) {
+            i = scan_string(chars, i)?;
+            continue;
+        }
+        match chars[i] {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Type arguments starting at the `<` at `start`, normalized like `<String, int?>`.
+fn scan_type_args(chars: &[char], start: usize) -> Option<(usize, String)> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            c if c.is_alphanumeric() || " _$,?.()\n".contains(c) => {}
+            _ => return None,
+        }
+        i += 1;
+    }
+    if i >= chars.len() {
+        return None;
+    }
+
+    let raw: String = chars[start..=i].iter().collect();
+    let words: Vec<&str> = raw.split_whitespace().collect();
+    let mut text = words.join(" ");
+    for (from, to) in [
+        (" ,", ","),
+        (" >", ">"),
+        (" )", ")"),
+        (" ?", "?"),
+        ("< ", "<"),
+        ("( ", "("),
+        (" <", "<"),
+        (", ", ","),
+    ] {
+        text = text.replace(from, to);
+    }
+    Some((i + 1, text.replace(',', ", ")))
+}
+
+/// Tokens compared without their layout, and without the spacing inside type arguments.
+fn same_tokens(a: &[Token], b: &[Token]) -> bool {
+    let key = |e: &Token| e.text.replace(' ', "");
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| key(a) == key(b))
+}
+
+#[derive(Debug)]
+enum Node {
+    Tok(Token),
+    Group(Token, Vec<Node>, Token),
+}
+
+impl Node {
+    fn first(&self) -> &Token {
+        match self {
+            Node::Tok(token) | Node::Group(token, _, _) => token,
+        }
+    }
+
+    fn last(&self) -> &Token {
+        match self {
+            Node::Tok(token) | Node::Group(_, _, token) => token,
+        }
+    }
+
+    fn is(&self, text: &str) -> bool {
+        matches!(self, Node::Tok(token) if token.text == text && token.kind != Kind::Str)
+    }
+
+    fn is_group(&self, open: &str) -> bool {
+        matches!(self, Node::Group(token, _, _) if token.text == open)
+    }
+
+    fn word(&self) -> Option<&str> {
+        match self {
+            Node::Tok(token) if token.kind == Kind::Word => Some(&token.text),
+            _ => None,
+        }
+    }
+}
+
+fn build_tree(tokens: Vec<Token>) -> Option<Vec<Node>> {
+    let mut stack: Vec<(Token, Vec<Node>)> = Vec::new();
+    let mut nodes = Vec::new();
+    for token in tokens {
+        let punct = token.kind == Kind::Punct;
+        match token.text.as_str() {
+            "(" | "[" | "{" if punct => {
+                stack.push((token, std::mem::take(&mut nodes)));
+            }
+            ")" | "]" | "}" if punct => {
+                let (open, parent) = stack.pop()?;
+                let expected = match open.text.as_str() {
+                    "(" => ")",
+                    "[" => "]",
+                    _ => "}",
+                };
+                if token.text != expected {
+                    return None;
+                }
+                let children = std::mem::replace(&mut nodes, parent);
+                nodes.push(Node::Group(open, children, token));
+            }
+            _ => nodes.push(Node::Tok(token)),
+        }
+    }
+    if stack.is_empty() {
+        Some(nodes)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Body {
+    TopLevel,
+    Class,
+    Enum,
+    Switch,
+    Function,
+}
+
+/// Lay out the declarations or statements of a block at `indent`.
+fn format_block(nodes: &[Node], indent: usize, body: Body, lines: &mut Vec<String>) {
+    let start = lines.len();
+    let pad = " ".repeat(indent);
+    let mut i = 0;
+
+    if body == Body::Enum {
+        let end = nodes.iter().position(|e| e.is(";"));
+        let constants = &nodes[..end.unwrap_or(nodes.len())];
+        let items = split_items(constants);
+        let count = items.items.len();
+        for (idx, item) in items.items.iter().enumerate() {
+            for comment in &item.comments {
+                lines.push(format!("{}{}", pad, comment.text));
+            }
+            let suffix = match (idx + 1 == count, end.is_some()) {
+                (true, true) => ";",
+                (true, false) if !items.trailing => "",
+                _ => ",",
+            };
+            // like other declarations, a constant's annotations get lines of their own
+            let mut start = 0;
+            while item.nodes.get(start).is_some_and(|e| e.is("@")) {
+                let end = annotation_end(item.nodes, start);
+                let text = print_nodes(&item.nodes[start..end], indent, indent);
+                lines.push(format!("{}{}", pad, text));
+                start = end;
+            }
+            let mut text = print_nodes(&item.nodes[start..], indent, indent) + suffix;
+            if let Some(comment) = &item.trailing_comment {
+                text = format!("{} {}", text, comment.text);
+            }
+            lines.push(format!("{}{}", pad, text));
+        }
+        i = end.map_or(nodes.len(), |e| e + 1);
+    }
+
+    let mut in_case = false;
+    let mut after_method = false;
+    while i < nodes.len() {
+        let first = nodes[i].first();
+        let blank = (first.newlines >= 2 || after_method) && lines.len() > start;
+        after_method = false;
+
+        if first.kind == Kind::Comment {
+            if first.newlines == 0 && lines.len() > start {
+                let last = lines.last_mut().unwrap();
+                last.push(' ');
+                last.push_str(&first.text);
+            } else {
+                if blank {
+                    lines.push(String::new());
+                }
+                let extra = if in_case { 2 } else { 0 };
+                lines.push(format!("{}{}", " ".repeat(indent + extra), first.text));
+            }
+            i += 1;
+            continue;
+        }
+        if blank {
+            lines.push(String::new());
+        }
+
+        // `@override` and other annotations go on their own line
+        if nodes[i].is("@") {
+            let end = annotation_end(nodes, i);
+            let extra = if in_case { 2 } else { 0 };
+            let text = print_nodes(&nodes[i..end], indent + extra, indent + extra);
+            lines.push(format!("{}{}", " ".repeat(indent + extra), text));
+            i = end;
+            continue;
+        }
+
+        if body == Body::Switch && matches!(nodes[i].word(), Some("case" | "default")) {
+            let end = (i..nodes.len())
+                .find(|e| nodes[*e].is(":"))
+                .unwrap_or(nodes.len() - 1);
+            let label = print_nodes(&nodes[i..end], indent, indent);
+            lines.push(format!("{}{}:", pad, label));
+            in_case = true;
+            i = end + 1;
+            continue;
+        }
+
+        let end = statement_end(nodes, i);
+        let indent = if in_case { indent + 2 } else { indent };
+        let method = format_statement(&nodes[i..end], indent, body, lines);
+        after_method = method && body != Body::Function;
+        i = end;
+    }
+}
+
+/// The end of the annotation starting with the `@` at `start`.
+fn annotation_end(nodes: &[Node], start: usize) -> usize {
+    let mut end = start + 1;
+    while end < nodes.len()
+        && (nodes[end].word().is_some() && end == start + 1
+            || nodes[end].is(".")
+            || nodes[end - 1].is(".")
+            || (nodes[end].is_group("(") && !nodes[end].first().space))
+    {
+        end += 1;
+    }
+    end
+}
+
+/// The end of the statement or declaration starting at `start`.
+fn statement_end(nodes: &[Node], start: usize) -> usize {
+    let mut i = start;
+    while i < nodes.len() {
+        if nodes[i].is(";") {
+            return i + 1;
+        }
+        if is_block(nodes, start, i) {
+            let next = nodes.get(i + 1).and_then(|e| e.word());
+            if !matches!(next, Some("else" | "catch" | "finally" | "on")) {
+                return i + 1;
+            }
+        }
+        i += 1;
+    }
+    nodes.len()
+}
+
+/// Whether the `{` group at `idx` is a block rather than a map, set or named parameters.
+fn is_block(nodes: &[Node], start: usize, idx: usize) -> bool {
+    if !nodes[idx].is_group("{") || idx == start {
+        return false;
+    }
+    match &nodes[idx - 1] {
+        Node::Group(open, _, _) => open.text == "(",
+        Node::Tok(token) if token.kind == Kind::Word => {
+            !token.text.starts_with('<')
+                && ![
+                    "return", "const", "yield", "await", "throw", "new", "in", "is", "as",
+                ]
+                .contains(&token.text.as_str())
+        }
+        _ => false,
+    }
+}
+
+/// Lay out one statement, returns whether it is a method with a non-empty
+/// block body, which `dart format` follows with a blank line.
+fn format_statement(nodes: &[Node], indent: usize, body: Body, lines: &mut Vec<String>) -> bool {
+    let pad = " ".repeat(indent);
+    let mut seg_start = 0;
+    let mut started = false;
+    let mut method = false;
+
+    for idx in 0..nodes.len() {
+        if !is_block(nodes, 0, idx) {
+            continue;
+        }
+        let header = &nodes[seg_start..idx];
+        print_segment(header, indent, started, lines);
+        started = true;
+        let last = lines.last_mut().unwrap();
+        if !header.is_empty() {
+            last.push(' ');
+        }
+
+        let children = match &nodes[idx] {
+            Node::Group(_, children, _) => children,
+            _ => unreachable!(),
+        };
+        if children.is_empty() {
+            last.push_str("{}");
+        } else {
+            last.push('{');
+            let words: Vec<&str> = header.iter().filter_map(|e| e.word()).collect();
+            let inner = if words.contains(&"enum") {
+                Body::Enum
+            } else if words.first() == Some(&"switch") {
+                Body::Switch
+            } else if ["class", "mixin", "extension"]
+                .iter()
+                .any(|e| words.contains(e))
+            {
+                Body::Class
+            } else {
+                Body::Function
+            };
+            format_block(children, indent + 2, inner, lines);
+            lines.push(format!("{}}}", pad));
+
+            let constructor = matches!(words.first(), Some(&"factory" | &"const"))
+                || header
+                    .first()
+                    .is_some_and(|e| e.first().text.starts_with(char::is_uppercase))
+                    && header.get(1).is_some_and(|e| e.is_group("(") || e.is("."));
+            method = (body == Body::Class || body == Body::Enum)
+                && inner == Body::Function
+                && !constructor
+                && header
+                    .iter()
+                    .any(|e| e.is_group("(") || e.word() == Some("get"));
+        }
+        seg_start = idx + 1;
+    }
+
+    let tail = &nodes[seg_start..];
+    if !tail.is_empty() {
+        print_segment(tail, indent, started, lines);
+    }
+    method
+}
+
+/// Print a statement part at `indent`, appended to the last line when `append`.
+fn print_segment(nodes: &[Node], indent: usize, append: bool, lines: &mut Vec<String>) {
+    let semicolon = nodes.last().is_some_and(|e| e.is(";"));
+    let nodes = if semicolon {
+        &nodes[..nodes.len() - 1]
+    } else {
+        nodes
+    };
+    let suffix = if semicolon { ";" } else { "" };
+
+    if append {
+        let last = lines.last_mut().unwrap();
+        if nodes.is_empty() {
+            last.push_str(suffix);
+            return;
+        }
+        last.push(' ');
+        let text = print_nodes(nodes, indent, last.chars().count()) + suffix;
+        let mut parts = text.split('\n');
+        last.push_str(parts.next().unwrap_or(""));
+        lines.extend(parts.map(String::from));
+    } else {
+        let text = print_nodes(nodes, indent, indent) + suffix;
+        let pad = " ".repeat(indent);
+        let mut parts = text.split('\n');
+        lines.push(format!("{}{}", pad, parts.next().unwrap_or("")));
+        lines.extend(parts.map(String::from));
+    }
+}
+
+fn print_nodes(nodes: &[Node], indent: usize, column: usize) -> String {
+    print_doc(&expr_doc(nodes), indent, column)
+}
+
+enum Doc {
+    Text(String),
+    /// A line break, or the text when its group fits on one line.
+    Line(&'static str),
+    HardLine,
+    Concat(Vec<Doc>),
+    Indent(usize, Box<Doc>),
+    Group(Box<Doc>),
+    /// The doc after a space, or on the next line when it only fits flat there.
+    Split(Box<Doc>),
+}
+
+fn text(text: impl Into<String>) -> Doc {
+    Doc::Text(text.into())
+}
+
+fn indent(width: usize, doc: Doc) -> Doc {
+    Doc::Indent(width, Box::new(doc))
+}
+
+fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Print the doc starting at `column`, breaking groups that don't fit in the page width.
+fn print_doc(doc: &Doc, indent: usize, column: usize) -> String {
+    let mut out = String::new();
+    let mut column = column;
+    let mut cmds: Vec<(usize, Mode, &Doc)> = vec![(indent, Mode::Break, doc)];
+    while let Some((ind, mode, doc)) = cmds.pop() {
+        match doc {
+            Doc::Text(value) => {
+                out.push_str(value);
+                column += value.chars().count();
+            }
+            Doc::Concat(docs) => cmds.extend(docs.iter().rev().map(|e| (ind, mode, e))),
+            Doc::Indent(width, doc) => cmds.push((ind + width, mode, doc)),
+            Doc::Group(doc) => {
+                let rest = WIDTH as isize - column as isize;
+                let flat = mode == Mode::Flat || fits(rest, doc, &cmds);
+                cmds.push((ind, if flat { Mode::Flat } else { Mode::Break }, doc));
+            }
+            Doc::Split(doc) => {
+                let here = WIDTH as isize - column as isize - 1;
+                let next = WIDTH as isize - ind as isize;
+                let stay = fits(here, doc, &cmds)
+                    || !fits(next, doc, &cmds) && fits_in(here, Mode::Break, doc, &cmds);
+                if mode == Mode::Flat || stay {
+                    out.push(' ');
+                    column += 1;
+                } else {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(ind));
+                    column = ind;
+                }
+                cmds.push((ind, mode, doc));
+            }
+            Doc::Line(value) if mode == Mode::Flat => {
+                out.push_str(value);
+                column += value.chars().count();
+            }
+            Doc::Line(_) | Doc::HardLine => {
+                out.truncate(out.trim_end_matches(' ').len());
+                out.push('\n');
+                out.push_str(&" ".repeat(ind));
+                column = ind;
+            }
+        }
+    }
+    out
+}
+
+/// Whether `doc` fits flat in `width`, together with what follows it up to
+/// the next line break.
+fn fits(width: isize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    fits_in(width, Mode::Flat, doc, rest)
+}
+
+/// Whether `doc` fits up to its first line break when printed in `mode`.
+fn fits_in(mut width: isize, mode: Mode, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut stack = vec![(mode, doc)];
+    let mut rest_idx = rest.len();
+    loop {
+        if width < 0 {
+            return false;
+        }
+        let (mode, doc) = match stack.pop() {
+            Some(next) => next,
+            None if rest_idx == 0 => return true,
+            None => {
+                rest_idx -= 1;
+                (rest[rest_idx].1, rest[rest_idx].2)
+            }
+        };
+        match doc {
+            Doc::Text(value) => width -= value.chars().count() as isize,
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|e| (mode, e))),
+            Doc::Indent(_, doc) | Doc::Group(doc) => stack.push((mode, doc)),
+            Doc::Split(_) if mode == Mode::Break => return true,
+            Doc::Split(doc) => {
+                width -= 1;
+                stack.push((mode, doc));
+            }
+            Doc::Line(value) if mode == Mode::Flat => width -= value.chars().count() as isize,
+            Doc::Line(_) => return true,
+            Doc::HardLine => return mode == Mode::Break,
+        }
+    }
+}
+
+const ASSIGNMENTS: &[&str] = &[
+    "=", "+=", "-=", "*=", "/=", "%=", "~/=", "&=", "|=", "^=", "<<=", "??=",
+];
+
+/// Binary operators from the lowest precedence up, all of one level split together.
+const BINARY: &[&[&str]] = &[
+    &["??"],
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["+", "-"],
+    &["*", "/", "%", "~/"],
+];
+
+fn expr_doc(nodes: &[Node]) -> Doc {
+    if nodes.is_empty() {
+        return text("");
+    }
+
+    // named arguments, map entries: `name: value`
+    if nodes.len() > 2
+        && nodes[1].is(":")
+        && !nodes[1].first().space
+        && matches!(nodes[0].first().kind, Kind::Word | Kind::Str)
+        && matches!(nodes[0], Node::Tok(_))
+    {
+        return Doc::Concat(vec![
+            text(format!("{}:", nodes[0].first().text)),
+            text(" "),
+            expr_doc(&nodes[2..]),
+        ]);
+    }
+    if nodes.len() > 1
+        && matches!(
+            nodes[0].word(),
+            Some("return" | "throw" | "await" | "yield")
+        )
+    {
+        return Doc::Concat(vec![
+            text(nodes[0].first().text.clone()),
+            text(" "),
+            expr_doc(&nodes[1..]),
+        ]);
+    }
+
+    if let Some(idx) = nodes
+        .iter()
+        .position(|e| ASSIGNMENTS.iter().any(|op| e.is(op)))
+    {
+        let op = &nodes[idx].first().text;
+        let rhs = &nodes[idx + 1..];
+        if is_block_like(rhs) {
+            return Doc::Concat(vec![
+                atoms(&nodes[..idx]),
+                text(format!(" {} ", op)),
+                expr_doc(rhs),
+            ]);
+        }
+        return Doc::Concat(vec![
+            atoms(&nodes[..idx]),
+            text(format!(" {}", op)),
+            indent(4, Doc::Concat(vec![group(Doc::Line(" ")), expr_doc(rhs)])),
+        ]);
+    }
+
+    if let Some(idx) = nodes.iter().position(|e| e.is("=>")) {
+        return Doc::Concat(vec![
+            atoms(&nodes[..idx]),
+            text(" =>"),
+            indent(4, Doc::Split(Box::new(expr_doc(&nodes[idx + 1..])))),
+        ]);
+    }
+
+    if let Some(question) = nodes.iter().position(|e| e.is("?") && e.first().space) {
+        let mut depth = 0;
+        let colon = (question + 1..nodes.len()).find(|idx| {
+            let node = &nodes[*idx];
+            if node.is("?") && node.first().space {
+                depth += 1;
+            } else if node.is(":") && node.first().space {
+                if depth == 0 {
+                    return true;
+                }
+                depth -= 1;
+            }
+            false
+        });
+        if let Some(colon) = colon {
+            return group(Doc::Concat(vec![
+                expr_doc(&nodes[..question]),
+                indent(
+                    4,
+                    Doc::Concat(vec![
+                        Doc::Line(" "),
+                        text("? "),
+                        expr_doc(&nodes[question + 1..colon]),
+                        Doc::Line(" "),
+                        text(": "),
+                        expr_doc(&nodes[colon + 1..]),
+                    ]),
+                ),
+            ]));
+        }
+    }
+
+    for ops in BINARY {
+        let positions: Vec<usize> = (1..nodes.len())
+            .filter(|idx| {
+                let token = nodes[*idx].first();
+                ops.iter().any(|op| nodes[*idx].is(op))
+                    && token.space
+                    && nodes.get(idx + 1).is_some_and(|e| e.first().space)
+            })
+            .collect();
+        if positions.is_empty() {
+            continue;
+        }
+
+        let mut parts = vec![expr_doc(&nodes[..positions[0]])];
+        let mut rest = Vec::new();
+        for (i, pos) in positions.iter().enumerate() {
+            let end = positions.get(i + 1).copied().unwrap_or(nodes.len());
+            rest.push(text(format!(" {}", nodes[*pos].first().text)));
+            rest.push(Doc::Line(" "));
+            rest.push(expr_doc(&nodes[pos + 1..end]));
+        }
+        parts.push(indent(4, Doc::Concat(rest)));
+        return group(Doc::Concat(parts));
+    }
+
+    chain_doc(nodes).unwrap_or_else(|| atoms(nodes))
+}
+
+/// Method chains like `(list ?? []).map(...).toList()` split before every `.`.
+fn chain_doc(nodes: &[Node]) -> Option<Doc> {
+    let first_dot = nodes.iter().position(|e| e.is(".") || e.is("?."))?;
+    let target = &nodes[..first_dot];
+    let primary = !target.is_empty()
+        && matches!(target[0].first().kind, Kind::Word | Kind::Str)
+        && target[1..]
+            .iter()
+            .all(|e| e.is_group("(") || e.is_group("[") || e.is("!"))
+        || target.len() == 1 && target[0].is_group("(");
+    if !primary {
+        return None;
+    }
+
+    let mut segments: Vec<&[Node]> = Vec::new();
+    let mut start = first_dot;
+    while start < nodes.len() {
+        if !(nodes[start].is(".") || nodes[start].is("?."))
+            || nodes.get(start + 1)?.word().is_none()
+        {
+            return None;
+        }
+        let mut end = start + 2;
+        while end < nodes.len()
+            && (nodes[end].is_group("(") || nodes[end].is_group("[") || nodes[end].is("!"))
+        {
+            end += 1;
+        }
+        segments.push(&nodes[start..end]);
+        start = end;
+    }
+
+    let is_call = |segment: &&[Node]| segment.iter().any(|e| e.is_group("("));
+    let mut kept = target.len();
+    let mut calls = segments.iter().skip_while(|e| !is_call(e));
+    let simple = target.len() == 1 && target[0].word().is_some();
+    let properties = segments.len() - calls.clone().count();
+    kept += segments[..properties]
+        .iter()
+        .map(|e| e.len())
+        .sum::<usize>();
+    let mut split: Vec<&[Node]> = Vec::new();
+    if simple && target[0].first().text.starts_with(char::is_uppercase)
+        || matches!(target[0].word(), Some("this" | "super"))
+    {
+        if let Some(call) = calls.next() {
+            kept += call.len();
+        }
+    }
+    split.extend(calls);
+
+    if !split.iter().any(is_call) || (simple && kept == 1 && split.len() == 1) {
+        return None;
+    }
+    let mut parts = Vec::new();
+    for segment in split {
+        parts.push(Doc::Line(""));
+        parts.push(atoms(segment));
+    }
+    Some(Doc::Concat(vec![
+        atoms(&nodes[..kept]),
+        group(indent(4, Doc::Concat(parts))),
+    ]))
+}
+
+/// A collection literal, optionally `const` or with type arguments.
+fn is_block_like(nodes: &[Node]) -> bool {
+    let mut nodes = nodes;
+    if nodes.first().is_some_and(|e| e.word() == Some("const")) {
+        nodes = &nodes[1..];
+    }
+    if nodes
+        .first()
+        .is_some_and(|e| e.first().text.starts_with('<'))
+    {
+        nodes = &nodes[1..];
+    }
+    nodes.len() == 1 && (nodes[0].is_group("[") || nodes[0].is_group("{"))
+}
+
+/// Nodes joined with the spacing `dart format` puts between them.
+fn atoms(nodes: &[Node]) -> Doc {
+    let mut docs = Vec::new();
+    for (idx, node) in nodes.iter().enumerate() {
+        if idx > 0 && need_space(nodes[idx - 1].last(), node.first()) {
+            docs.push(text(" "));
+        }
+        match node {
+            Node::Tok(token) => docs.push(text(token.text.clone())),
+            Node::Group(open, children, close) => docs.push(group_doc(open, children, close)),
+        }
+    }
+    Doc::Concat(docs)
+}
+
+fn need_space(prev: &Token, next: &Token) -> bool {
+    let (p, n) = (prev.text.as_str(), next.text.as_str());
+    let punct = |token: &Token| token.kind == Kind::Punct;
+    if punct(next) && matches!(n, "," | ";" | ")" | "]" | "}" | "." | "?." | "..") {
+        return false;
+    }
+    if punct(prev) && matches!(p, "(" | "[" | "{" | "." | "?." | ".." | "@") {
+        return false;
+    }
+    if punct(next) && matches!(n, "!" | "?" | ":" | "++" | "--") {
+        return next.space;
+    }
+    if punct(prev) && matches!(p, "!" | "-" | "++" | "--") {
+        return next.space;
+    }
+    if punct(next) && (n == "(" || n == "[") {
+        return match prev.kind {
+            Kind::Word => KEYWORDS.contains(&p),
+            Kind::Punct => !matches!(p, ")" | "]" | "}"),
+            _ => false,
+        };
+    }
+    if punct(next) && n == "{" {
+        return !p.starts_with('<');
+    }
+    true
+}
+
+struct Item<'a> {
+    comments: Vec<&'a Token>,
+    nodes: &'a [Node],
+    trailing_comment: Option<&'a Token>,
+}
+
+struct Items<'a> {
+    items: Vec<Item<'a>>,
+    trailing: bool,
+    comments: Vec<&'a Token>,
+}
+
+/// Split the contents of a bracket at its commas, keeping the comments
+/// before each item and a comment on the line of its comma.
+fn split_items(nodes: &[Node]) -> Items<'_> {
+    let mut items = Vec::new();
+    let mut comments = Vec::new();
+    let mut start = None;
+    let mut after_comma = false;
+    for (idx, node) in nodes.iter().enumerate() {
+        let token = node.first();
+        if matches!(node, Node::Tok(_)) && token.kind == Kind::Comment {
+            if after_comma && token.newlines == 0 {
+                if let Some(item) = items.last_mut() {
+                    let item: &mut Item = item;
+                    item.trailing_comment = Some(token);
+                    continue;
+                }
+            }
+            if start.is_none() {
+                comments.push(token);
+            }
+            continue;
+        }
+        if node.is(",") {
+            items.push(Item {
+                comments: std::mem::take(&mut comments),
+                nodes: &nodes[start.unwrap_or(idx)..idx],
+                trailing_comment: None,
+            });
+            start = None;
+            after_comma = true;
+            continue;
+        }
+        after_comma = false;
+        if start.is_none() {
+            start = Some(idx);
+        }
+    }
+
+    let trailing = start.is_none() && !items.is_empty();
+    if let Some(start) = start {
+        let end = (start..nodes.len())
+            .rev()
+            .find(|idx| nodes[*idx].first().kind != Kind::Comment)
+            .map_or(nodes.len(), |e| e + 1);
+        items.push(Item {
+            comments: std::mem::take(&mut comments),
+            nodes: &nodes[start..end],
+            trailing_comment: None,
+        });
+        comments.extend(nodes[end..].iter().map(|e| e.first()));
+    }
+    Items {
+        items,
+        trailing,
+        comments,
+    }
+}
+
+fn group_doc(open: &Token, children: &[Node], close: &Token) -> Doc {
+    let (open, close) = (open.text.as_str(), close.text.as_str());
+    if children.is_empty() {
+        return text(format!("{}{}", open, close));
+    }
+
+    // a for loop header stays on one line
+    if open == "(" && children.iter().any(|e| e.is(";")) {
+        let mut docs = vec![text("(")];
+        for (idx, part) in children.split(|e| e.is(";")).enumerate() {
+            if idx > 0 {
+                docs.push(text(if part.is_empty() { ";" } else { "; " }));
+            }
+            docs.push(atoms(part));
+        }
+        docs.push(text(")"));
+        return Doc::Concat(docs);
+    }
+
+    let items = split_items(children);
+    let count = items.items.len();
+    let commented = !items.comments.is_empty()
+        || items
+            .items
+            .iter()
+            .any(|e| !e.comments.is_empty() || e.trailing_comment.is_some());
+
+    if items.trailing || commented {
+        let mut body = Vec::new();
+        for (idx, item) in items.items.iter().enumerate() {
+            for comment in &item.comments {
+                body.push(Doc::HardLine);
+                body.push(text(comment.text.clone()));
+            }
+            body.push(Doc::HardLine);
+            body.push(expr_doc(item.nodes));
+            if idx + 1 < count || items.trailing {
+                body.push(text(","));
+            }
+            if let Some(comment) = item.trailing_comment {
+                body.push(text(format!(" {}", comment.text)));
+            }
+        }
+        for comment in &items.comments {
+            body.push(Doc::HardLine);
+            body.push(text(comment.text.clone()));
+        }
+        return Doc::Concat(vec![
+            text(open),
+            indent(2, Doc::Concat(body)),
+            Doc::HardLine,
+            text(close),
+        ]);
+    }
+
+    let mut joined = Vec::new();
+    for (idx, item) in items.items.iter().enumerate() {
+        if idx > 0 {
+            joined.push(text(","));
+            joined.push(Doc::Line(" "));
+        }
+        joined.push(expr_doc(item.nodes));
+    }
+
+    if open != "(" {
+        return group(Doc::Concat(vec![
+            text(open),
+            indent(2, Doc::Concat(vec![Doc::Line(""), Doc::Concat(joined)])),
+            Doc::Line(""),
+            text(close),
+        ]));
+    }
+    if count == 1 && (is_block_like(items.items[0].nodes)) {
+        return Doc::Concat(vec![text("("), Doc::Concat(joined), text(")")]);
+    }
+    group(Doc::Concat(vec![
+        text("("),
+        indent(
+            4,
+            Doc::Concat(vec![Doc::Line(""), group(Doc::Concat(joined))]),
+        ),
+        text(")"),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_dart;
+
+    /// `source` is generated with `--formatter none`, `golden` is how
+    /// `dart format` lays it out. Formatting the golden keeps it as is.
+    fn check(source: &str, golden: &str) {
+        assert_eq!(format_dart(source), golden);
+        assert_eq!(format_dart(golden), golden);
+    }
+
+    #[test]
+    fn formats_model() {
+        check(
+            include_str!("../tests/formatter/model.dart"),
+            include_str!("../tests/formatter/model.golden.dart"),
+        );
+    }
+
+    #[test]
+    fn formats_enum() {
+        check(
+            include_str!("../tests/formatter/enum.dart"),
+            include_str!("../tests/formatter/enum.golden.dart"),
+        );
+    }
+
+    #[test]
+    fn formats_sealed_union() {
+        check(
+            include_str!("../tests/formatter/sealed.dart"),
+            include_str!("../tests/formatter/sealed.golden.dart"),
+        );
+    }
+
+    #[test]
+    fn formats_freezed_model() {
+        check(
+            include_str!("../tests/formatter/freezed.dart"),
+            include_str!("../tests/formatter/freezed.golden.dart"),
+        );
+    }
+
+    #[test]
+    fn formats_i18n() {
+        check(
+            include_str!("../tests/formatter/i18n_const_key.dart"),
+            include_str!("../tests/formatter/i18n_const_key.golden.dart"),
+        );
+        check(
+            include_str!("../tests/formatter/i18n_en.dart"),
+            include_str!("../tests/formatter/i18n_en.golden.dart"),
+        );
+        check(
+            include_str!("../tests/formatter/i18n_index.dart"),
+            include_str!("../tests/formatter/i18n_index.golden.dart"),
+        );
+    }
+}
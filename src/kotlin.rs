@@ -0,0 +1,241 @@
+use convert_case::{Case, Casing};
+use serde_json::Value;
+
+use crate::model::{self, Enum, Field, Model, Type};
+use crate::utils;
+
+/// Hard keywords kotlin only accepts as a name in backticks.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+/// The `.kt` file of the model, the data classes of its children and its
+/// enums are declared next to it. The path is relative to the output dir.
+pub fn render(model: &Model, package: Option<&str>) -> (String, String) {
+    let models = model.flatten();
+    let mut source = model::header() + "\n";
+    if let Some(package) = package {
+        source.push_str(&format!("package {}\n\n", package));
+    }
+    let values = models.iter().flat_map(|e| &e.enums).any(|e| e.has_values());
+    if values {
+        source.push_str("import kotlinx.serialization.KSerializer\n");
+    }
+    source.push_str("import kotlinx.serialization.SerialName\n");
+    source.push_str("import kotlinx.serialization.Serializable\n");
+    if values {
+        source.push_str("import kotlinx.serialization.SerializationException\n");
+        source.push_str("import kotlinx.serialization.encoding.Decoder\n");
+        source.push_str("import kotlinx.serialization.encoding.Encoder\n");
+    }
+    if values
+        || models
+            .iter()
+            .flat_map(|e| &e.fields)
+            .any(|e| e.ty.is_dynamic())
+    {
+        source.push_str("import kotlinx.serialization.json.JsonElement\n");
+    }
+    if values {
+        source.push_str("import kotlinx.serialization.json.JsonPrimitive\n");
+    }
+
+    for model in &models {
+        if model.has_class() {
+            source.push('\n');
+            source.push_str(&data_class(model));
+        }
+        for item in &model.enums {
+            source.push('\n');
+            source.push_str(&enum_class(item));
+        }
+    }
+
+    let file = utils::join_dir(&model.dir, &format!("{}.kt", model.class_name));
+    (file, source)
+}
+
+fn data_class(model: &Model) -> String {
+    if model.fields.is_empty() {
+        return format!("@Serializable\nclass {}\n", model.class_name);
+    }
+    let properties: String = model.fields.iter().map(property).collect();
+    format!(
+        "@Serializable\ndata class {}(\n{})\n",
+        model.class_name, properties
+    )
+}
+
+fn property(field: &Field) -> String {
+    let mut result = String::from("    ");
+    if field.key != field.name {
+        result.push_str(&format!("@SerialName({}) ", literal(&field.key)));
+    }
+    result.push_str(&format!(
+        "val {}: {}",
+        identifier(&field.name),
+        kotlin_type(field)
+    ));
+    if let Some(value) = default_value(field) {
+        result.push_str(&format!(" = {}", value));
+    }
+    result.push_str(",\n");
+    result
+}
+
+/// The declared type, dates, durations and uris stay strings and longs as
+/// kotlinx has no serializer for them built in.
+fn kotlin_type(field: &Field) -> String {
+    let name = type_name(&field.ty);
+    if field.nullable {
+        format!("{}?", name)
+    } else {
+        name
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::String | Type::Uri | Type::DateTime { millis: false } => String::from("String"),
+        Type::Int | Type::Duration | Type::DateTime { millis: true } => String::from("Long"),
+        Type::Double | Type::Num => String::from("Double"),
+        Type::Bool => String::from("Boolean"),
+        Type::Dynamic => String::from("JsonElement"),
+        Type::List(element, nullable) => {
+            let nullable = if *nullable || element.is_dynamic() {
+                "?"
+            } else {
+                ""
+            };
+            format!("List<{}{}>", type_name(element), nullable)
+        }
+        Type::Model(name) | Type::Enum(name) | Type::Named(name) => name.clone(),
+    }
+}
+
+/// The constructor default, lists default to empty and optional fields to null.
+fn default_value(field: &Field) -> Option<String> {
+    if let Type::List(..) = field.ty {
+        return Some(String::from("emptyList()"));
+    }
+    match (&field.default, &field.ty) {
+        (Some(value), Type::Enum(name)) => Some(format!("{}.{}", name, member(value))),
+        (Some(Value::Number(number)), Type::Double | Type::Num) if number.is_i64() => {
+            Some(format!("{}.0", number))
+        }
+        (Some(value @ (Value::Number(_) | Value::Bool(_))), _) => Some(value.to_string()),
+        (Some(Value::String(value)), _) => Some(literal(value)),
+        _ if field.nullable && !field.required => Some(String::from("null")),
+        _ => None,
+    }
+}
+
+fn enum_class(item: &Enum) -> String {
+    if item.has_values() {
+        return value_enum_class(item);
+    }
+    let members: String = item
+        .values
+        .iter()
+        .map(|value| {
+            let wire = match value {
+                Value::String(value) => value.clone(),
+                _ => value.to_string(),
+            };
+            format!("    @SerialName({}) {},\n", literal(&wire), member(value))
+        })
+        .collect();
+    format!(
+        "@Serializable\nenum class {} {{\n{}}}\n",
+        item.name, members
+    )
+}
+
+/// `@SerialName` only names strings, the serializer compares the
+/// `JsonElement` of every member instead.
+fn value_enum_class(item: &Enum) -> String {
+    let name = &item.name;
+    let members: String = item
+        .values
+        .iter()
+        .map(|value| {
+            format!(
+                "    {}(JsonPrimitive({})),\n",
+                member(value),
+                primitive(value)
+            )
+        })
+        .collect();
+    format!(
+        r#"@Serializable(with = {name}Serializer::class)
+enum class {name}(val value: JsonElement) {{
+{members}}}
+
+object {name}Serializer : KSerializer<{name}> {{
+    override val descriptor = JsonElement.serializer().descriptor
+
+    override fun deserialize(decoder: Decoder): {name} {{
+        val element = decoder.decodeSerializableValue(JsonElement.serializer())
+        return {name}.values().firstOrNull {{ it.value == element }}
+            ?: throw SerializationException("unknown {name} $element")
+    }}
+
+    override fun serialize(encoder: Encoder, value: {name}) {{
+        encoder.encodeSerializableValue(JsonElement.serializer(), value.value)
+    }}
+}}
+"#
+    )
+}
+
+/// The argument of the `JsonPrimitive` holding an enum value.
+fn primitive(value: &Value) -> String {
+    match value {
+        Value::String(value) => literal(value),
+        _ => value.to_string(),
+    }
+}
+
+/// The UPPER_SNAKE_CASE member of an enum wire value.
+fn member(value: &Value) -> String {
+    utils::enum_member(value).to_case(Case::UpperSnake)
+}
+
+fn identifier(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn literal(value: &str) -> String {
+    model::string_literal(value, '"', &['$'])
+}
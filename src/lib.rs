@@ -26,12 +26,36 @@ pub fn cli() -> Command {
             Command::new("build")
                 .about("Build something form here")
                 .subcommand_required(true)
-                .subcommand(Command::new("json").about("Build dart model from json"))
+                .subcommand(
+                    Command::new("json")
+                        .about("Build dart model from json")
+                        .arg(arg!(--schema <DIR> "Build from json schema files in the dir"))
+                        .arg(arg!(-o --out <DIR> "The output dir of the models"))
+                        .arg(arg!(--with <LIST> "Extra members: copyWith,equality,toString"))
+                        .arg(arg!(-w --watch "Rebuild when the sources change"))
+                        .arg(arg!(-f --force "Rewrite every model, ignoring the build cache"))
+                        .arg(arg!(--formatter <NAME> "Format the output with builtin, dart or none"))
+                        .arg(arg!(--lang <LANG> "The language of the models: dart, kotlin, rust, swift or typescript"))
+                        .arg(arg!(--package <NAME> "The package of the kotlin models"))
+                        .arg(arg!(--zod "Add zod schemas to the typescript models"))
+                        .arg(arg!(--style <STYLE> "The dart model style: plain, json_serializable or freezed")),
+                )
+                .subcommand(
+                    Command::new("openapi")
+                        .about("Build dart model from openapi 3 spec")
+                        .arg(arg!(-s --spec <FILE> "The openapi spec file, yaml or json"))
+                        .arg(arg!(-o --out <DIR> "The output dir of the models"))
+                        .arg(arg!(--with <LIST> "Extra members: copyWith,equality,toString"))
+                        .arg(arg!(-f --force "Rewrite every model, ignoring the build cache"))
+                        .arg(arg!(--formatter <NAME> "Format the output with builtin, dart or none")),
+                )
                 .subcommand(
                     Command::new("translate")
                         .about("Build translation from json or csv")
                         .arg(arg!(--from <FROM> "The translate source"))
-                        .arg(arg!(--to <TO> "The translate to")),
+                        .arg(arg!(--to <TO> "The translate to"))
+                        .arg(arg!(--formatter <NAME> "Format the output with builtin, dart or none"))
+                        .arg(arg!(-w --watch "Rebuild when the sources change")),
                 )
                 .subcommand(
                     Command::new("icon")
@@ -39,6 +63,19 @@ pub fn cli() -> Command {
                     .arg(arg!(--platforms <PLATFORMS> "The platforms for icons"))
                     .arg(arg!(--file <FILE> "The icon source file location"))
                     .arg(arg!(--out <PATH> "The output path for the flutter project"))
+                    .arg(arg!(-w --watch "Rebuild when the icon changes"))
             ),
         )
+        .subcommand(
+            Command::new("check")
+                .about("Check data against the models")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("json")
+                        .about("Check captured json payloads against a model of the json samples")
+                        .arg(arg!(-m --model <NAME> "The model, a sample name or dir/name").required(true))
+                        .arg(arg!(-p --payload <FILES> ... "The payload files or dirs of them").num_args(1..).required(true))
+                        .arg_required_else_help(true),
+                ),
+        )
 }
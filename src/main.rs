@@ -1,9 +1,22 @@
 mod tmpl;
 mod utils;
 mod builder;
+mod cache;
+mod checker;
+mod config;
 mod creater;
+mod formatter;
+mod kotlin;
+mod model;
+mod openapi;
+mod rust;
+mod schema;
+mod swift;
+mod typescript;
+mod watcher;
 
 use crate::builder::build_project;
+use crate::checker::check_project;
 use crate::creater::create_project;
 
 fn main() {
@@ -12,6 +25,7 @@ fn main() {
     match matches.subcommand() {
         Some(("create", sub_matches)) => create_project(sub_matches),
         Some(("build", sub_matches)) => build_project(sub_matches),
+        Some(("check", sub_matches)) => check_project(sub_matches),
         Some((name, _)) => {
             // println!("Unknow command {}", name);
             let err = xtools::cli().error(clap::error::ErrorKind::InvalidSubcommand,format!("Unknow command {}", name));
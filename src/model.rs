@@ -0,0 +1,224 @@
+use serde_json::Value;
+
+use crate::tmpl;
+use crate::utils::{self, DartInfo, FieldInfo};
+
+/// The language models are generated in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Lang {
+    Dart,
+    Kotlin,
+    Rust,
+    Swift,
+    TypeScript,
+}
+
+impl Lang {
+    pub fn from_name(name: &str) -> Option<Lang> {
+        match name {
+            "dart" => Some(Lang::Dart),
+            "kotlin" | "kt" => Some(Lang::Kotlin),
+            "rust" | "rs" => Some(Lang::Rust),
+            "swift" => Some(Lang::Swift),
+            "typescript" | "ts" => Some(Lang::TypeScript),
+            _ => None,
+        }
+    }
+}
+
+/// The generated file banner, without the blank and trailing space that only
+/// `dart format` cleans up.
+pub fn header() -> String {
+    tmpl::HEADER
+        .trim_start()
+        .lines()
+        .map(|e| format!("{}\n", e.trim_end()))
+        .collect()
+}
+
+/// A string literal between `quote`s, `escaped` are the characters other than
+/// backslashes and quotes the language escapes with a backslash.
+pub fn string_literal(value: &str, quote: char, escaped: &[char]) -> String {
+    let mut result = String::from(quote);
+    for c in value.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            _ if c == '\\' || c == quote || escaped.contains(&c) => {
+                result.push('\\');
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+    result.push(quote);
+    result
+}
+
+/// A parsed model free of dart types and names, what every language other
+/// than dart is generated from. Class names keep the configured prefix and
+/// suffix, field names are lowerCamelCase.
+pub struct Model {
+    /// The name the model's file is named after.
+    pub name: String,
+    pub class_name: String,
+    pub dir: String,
+    /// Children and models referenced with `[]name`, as `DartInfo::imports`.
+    pub imports: Vec<String>,
+    pub fields: Vec<Field>,
+    pub children: Vec<Model>,
+    pub enums: Vec<Enum>,
+}
+
+pub struct Field {
+    pub name: String,
+    /// The key in json.
+    pub key: String,
+    pub ty: Type,
+    pub required: bool,
+    /// Whether the field may hold null, lists are never null and default to empty.
+    pub nullable: bool,
+    /// The `d@` default, the sample value of the field.
+    pub default: Option<Value>,
+}
+
+/// The type of a field. Only dart converts dates, durations and uris, the
+/// other languages keep the json type.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Type {
+    String,
+    Int,
+    Double,
+    Num,
+    Bool,
+    /// Any json value.
+    Dynamic,
+    /// A date time stored as an ISO-8601 string, or as epoch milliseconds.
+    DateTime {
+        millis: bool,
+    },
+    /// Milliseconds in json.
+    Duration,
+    Uri,
+    Model(String),
+    Enum(String),
+    /// A list, `bool` is whether the elements may be null.
+    List(Box<Type>, bool),
+    /// A type hint xtools doesn't know, used as written.
+    Named(String),
+}
+
+pub struct Enum {
+    pub name: String,
+    pub values: Vec<Value>,
+}
+
+impl Model {
+    pub fn from_dart(info: &DartInfo) -> Model {
+        if info.union.is_some() {
+            println!(
+                "\t  warning: the variants of `{}` are only generated for dart",
+                info.name
+            );
+        }
+        Model {
+            name: info.name.clone(),
+            class_name: utils::model_name(&info.name),
+            dir: info.dir.clone(),
+            imports: info.imports.clone(),
+            fields: info.fields.iter().map(Field::from_dart).collect(),
+            children: info.children.iter().map(Model::from_dart).collect(),
+            enums: info
+                .enums
+                .iter()
+                .map(|e| Enum {
+                    name: e.name.clone(),
+                    values: e.values.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether a class is declared for the model, a model of only enums
+    /// declares just the enums.
+    pub fn has_class(&self) -> bool {
+        !self.fields.is_empty() || self.enums.is_empty()
+    }
+
+    /// The names of the class and enums the model declares.
+    pub fn declarations(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        if self.has_class() {
+            result.push(self.class_name.clone());
+        }
+        result.extend(self.enums.iter().map(|e| e.name.clone()));
+        result
+    }
+
+    /// The model followed by all its children, depth first.
+    pub fn flatten(&self) -> Vec<&Model> {
+        let mut result = vec![self];
+        for child in &self.children {
+            result.extend(child.flatten());
+        }
+        result
+    }
+}
+
+impl Field {
+    pub fn from_dart(field: &FieldInfo) -> Field {
+        let nullable = matches!(field.ty, Type::Dynamic)
+            || !matches!(field.ty, Type::List(..))
+                && (field.nullable || !(field.required || field.default));
+
+        Field {
+            name: utils::plain_identifier(&field.name).to_string(),
+            key: field.key.clone(),
+            default: if field.default {
+                serde_json::from_str(&field.value).ok()
+            } else {
+                None
+            },
+            ty: field.ty.clone(),
+            required: field.required,
+            nullable,
+        }
+    }
+}
+
+impl Type {
+    /// Types a `d@` default can be written for as a literal, scalars and enums.
+    pub fn takes_default(&self) -> bool {
+        matches!(
+            self,
+            Type::String | Type::Int | Type::Double | Type::Num | Type::Bool | Type::Enum(_)
+        )
+    }
+
+    /// Whether the type is any json value, or a list of them.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            Type::Dynamic => true,
+            Type::List(element, _) => element.is_dynamic(),
+            _ => false,
+        }
+    }
+
+    /// The model or enum the type refers to, also as the element of a list.
+    pub fn used_type(&self) -> Option<&str> {
+        match self {
+            Type::Model(name) | Type::Enum(name) => Some(name),
+            Type::List(element, _) => element.used_type(),
+            _ => None,
+        }
+    }
+}
+
+impl Enum {
+    /// Whether a value other than a string is a member, which needs its own
+    /// serializer to keep the json type.
+    pub fn has_values(&self) -> bool {
+        !self.values.iter().all(|e| e.is_string())
+    }
+}
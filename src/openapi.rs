@@ -0,0 +1,52 @@
+use convert_case::Casing;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::schema;
+use crate::utils::DartInfo;
+
+const SCHEMA_REF: &str = "#/components/schemas/";
+
+/// Turn every entry of `components/schemas` into a model, the spec can be yaml or json.
+pub fn parse_spec(content: &str) -> Vec<DartInfo> {
+    let spec: Value =
+        serde_yaml::from_str(content).unwrap_or_else(|e| panic!("invalid openapi spec: {}", e));
+    let schemas = match spec
+        .pointer("/components/schemas")
+        .and_then(|e| e.as_object())
+    {
+        Some(schemas) => schemas,
+        None => {
+            println!("no components/schemas in spec");
+            return Vec::new();
+        }
+    };
+
+    let docs: HashMap<String, Value> = schemas
+        .iter()
+        .map(|(name, value)| (doc_name(name), rewrite_refs(value)))
+        .collect();
+
+    schema::parse_documents(&docs, &HashMap::new())
+}
+
+fn doc_name(name: &str) -> String {
+    name.to_case(convert_case::Case::Snake)
+}
+
+/// Point `#/components/schemas/Name` refs at the document of that schema.
+fn rewrite_refs(value: &Value) -> Value {
+    match value {
+        Value::String(val) => match val.strip_prefix(SCHEMA_REF) {
+            Some(name) => Value::String(doc_name(name)),
+            None => value.clone(),
+        },
+        Value::Array(items) => Value::Array(items.iter().map(rewrite_refs).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), rewrite_refs(value)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
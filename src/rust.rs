@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use convert_case::{Case, Casing};
+use serde_json::Value;
+
+use crate::model::{self, Enum, Field, Model, Type};
+use crate::utils;
+
+/// Keywords rust only accepts as a name in the raw `r#` form.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// What the module of one input file needs to know of the others.
+#[derive(Default)]
+pub struct Exports {
+    /// Where every model and its children are declared, keyed by model name:
+    /// the model of the input file holding it and the types it declares.
+    types: HashMap<String, (String, Vec<String>)>,
+    /// The modules every dir declares.
+    dirs: HashMap<String, Vec<String>>,
+}
+
+pub fn exports(models: &[Model]) -> Exports {
+    let mut types = HashMap::new();
+    for file in models {
+        for model in file.flatten() {
+            types.insert(
+                model.name.clone(),
+                (file.name.clone(), model.declarations()),
+            );
+        }
+    }
+    Exports {
+        types,
+        dirs: dir_modules(models),
+    }
+}
+
+/// Render the model with its children and enums into one module named after
+/// the input file, returning the file path relative to the output dir. A
+/// dir of the same name can't sit next to it, the module is then its `mod.rs`.
+pub fn render(
+    model: &Model,
+    dirs: &HashMap<String, Vec<String>>,
+    exports: &Exports,
+) -> (String, String) {
+    let models = model.flatten();
+    let own_dir = own_dir(&model.dir, &model.name, &exports.dirs);
+    let mut source = model::header() + "\n";
+    if let Some((_, names)) = own_dir {
+        source.push_str(&declarations(names));
+        source.push('\n');
+    }
+    source.push_str("use serde::{Deserialize, Serialize};\n");
+
+    // children are declared in the same module, models of other files are imported
+    let local: Vec<&str> = models.iter().map(|e| e.name.as_str()).collect();
+    let used: Vec<&str> = models
+        .iter()
+        .flat_map(|e| &e.fields)
+        .filter_map(|e| e.ty.used_type())
+        .collect();
+    let imports: Vec<String> = models
+        .iter()
+        .flat_map(|e| e.imports.iter().map(move |import| (e, import)))
+        .filter(|(_, import)| !local.contains(&import.rsplit('/').next().unwrap_or(import)))
+        .flat_map(|(from, import)| {
+            let file = |name: &str| {
+                let found = exports.types.get(name);
+                found.map_or(name, |e| e.0.as_str()).to_string()
+            };
+            utils::resolve_imports(&from.dir, std::slice::from_ref(import), dirs, file)
+        })
+        .flat_map(|(name, path)| {
+            let types = exports
+                .types
+                .get(&name)
+                .map(|e| e.1.clone())
+                .unwrap_or_default();
+            let path = module_path(&path);
+            types
+                .into_iter()
+                .filter(|e| used.contains(&e.as_str()))
+                .map(move |e| format!("use {}::{};\n", path, e))
+        })
+        .collect();
+    let mut imports: Vec<String> = imports;
+    imports.sort();
+    imports.dedup();
+    for import in imports {
+        source.push_str(&import);
+    }
+
+    for model in &models {
+        if model.has_class() {
+            source.push('\n');
+            source.push_str(&structure(model));
+        }
+        for item in &model.enums {
+            source.push('\n');
+            source.push_str(&enumeration(item));
+        }
+    }
+
+    let file = match own_dir {
+        Some((dir, _)) => utils::join_dir(dir, "mod.rs"),
+        None => utils::join_dir(&model.dir, &format!("{}.rs", module_name(&model.name))),
+    };
+    (file, source)
+}
+
+/// A `mod.rs` for every dir, declaring its input file modules and sub dirs,
+/// but the ones an input file module is written to.
+pub fn modules(models: &[Model]) -> Vec<(String, String)> {
+    let dirs = dir_modules(models);
+    let owned: Vec<&String> = models
+        .iter()
+        .filter_map(|e| own_dir(&e.dir, &e.name, &dirs).map(|(dir, _)| dir))
+        .collect();
+    let mut result: Vec<(String, String)> = dirs
+        .iter()
+        .filter(|(dir, _)| !owned.contains(dir))
+        .map(|(dir, names)| {
+            (
+                utils::join_dir(dir, "mod.rs"),
+                model::header() + "\n" + &declarations(names),
+            )
+        })
+        .collect();
+    result.sort();
+    result
+}
+
+/// The dir below `dir` named like the input file module `name`, with the modules it declares.
+fn own_dir<'a>(
+    dir: &str,
+    name: &str,
+    dirs: &'a HashMap<String, Vec<String>>,
+) -> Option<(&'a String, &'a Vec<String>)> {
+    dirs.iter().find(|(path, _)| {
+        let (parent, last) = path.rsplit_once('/').unwrap_or(("", path));
+        !path.is_empty() && parent == dir && module_name(last) == module_name(name)
+    })
+}
+
+fn declarations(names: &[String]) -> String {
+    names.iter().map(|e| format!("pub mod {};\n", e)).collect()
+}
+
+/// The input file modules and sub dirs of every dir.
+fn dir_modules(models: &[Model]) -> HashMap<String, Vec<String>> {
+    let mut dirs: HashMap<String, Vec<String>> = HashMap::new();
+    for model in models {
+        dirs.entry(model.dir.clone())
+            .or_default()
+            .push(module_name(&model.name));
+        let mut dir = model.dir.as_str();
+        while !dir.is_empty() {
+            let (parent, name) = dir.rsplit_once('/').unwrap_or(("", dir));
+            dirs.entry(parent.to_string())
+                .or_default()
+                .push(module_name(name));
+            dir = parent;
+        }
+    }
+
+    for names in dirs.values_mut() {
+        names.sort();
+        names.dedup();
+    }
+    dirs
+}
+
+/// `super::` paths from a model's module to the module of `path`, a file
+/// path relative to the model's dir.
+fn module_path(path: &str) -> String {
+    let mut result = String::from("super");
+    for part in path.split('/') {
+        if part == ".." {
+            result.push_str("::super");
+        } else {
+            result.push_str(&format!("::{}", module_name(part)));
+        }
+    }
+    result
+}
+
+fn module_name(name: &str) -> String {
+    identifier(&snake_name(name))
+}
+
+/// A snake_case name, with invalid characters dropped.
+fn snake_name(name: &str) -> String {
+    let name: String = name
+        .to_case(Case::Snake)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("k{}", name)
+    } else {
+        name
+    }
+}
+
+fn structure(model: &Model) -> String {
+    let derive = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n";
+    if model.fields.is_empty() {
+        return format!("{}pub struct {} {{}}\n", derive, model.class_name);
+    }
+
+    let prefix = model.class_name.to_case(Case::Snake);
+    let mut fields = String::new();
+    let mut defaults = String::new();
+    for field in &model.fields {
+        let name = field_name(field);
+        let mut attributes = Vec::new();
+        if name.trim_start_matches("r#") != field.key {
+            attributes.push(format!("rename = {}", literal(&field.key)));
+        }
+        if let (Some(value), false) = (default_value(field), field.required) {
+            let function = format!("default_{}_{}", prefix, name.trim_start_matches("r#"));
+            attributes.push(format!("default = {}", literal(&function)));
+            defaults.push_str(&format!(
+                "\nfn {}() -> {} {{\n    {}\n}}\n",
+                function,
+                rust_type(field),
+                value
+            ));
+        } else if !field.required
+            && (field.default.is_some() || matches!(field.ty, Type::List(..) | Type::Dynamic))
+        {
+            attributes.push(String::from("default"));
+        }
+
+        if !attributes.is_empty() {
+            fields.push_str(&format!("    #[serde({})]\n", attributes.join(", ")));
+        }
+        fields.push_str(&format!("    pub {}: {},\n", name, rust_type(field)));
+    }
+    format!(
+        "{}pub struct {} {{\n{}}}\n{}",
+        derive, model.class_name, fields, defaults
+    )
+}
+
+fn field_name(field: &Field) -> String {
+    identifier(&snake_name(&field.name))
+}
+
+fn rust_type(field: &Field) -> String {
+    let name = type_name(&field.ty);
+    if field.nullable && field.ty != Type::Dynamic {
+        format!("Option<{}>", name)
+    } else {
+        name
+    }
+}
+
+/// No chrono or url types, the output only depends on serde and serde_json.
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::String | Type::Uri | Type::DateTime { millis: false } => String::from("String"),
+        Type::Int | Type::Duration | Type::DateTime { millis: true } => String::from("i64"),
+        Type::Double | Type::Num => String::from("f64"),
+        Type::Bool => String::from("bool"),
+        Type::Dynamic => String::from("serde_json::Value"),
+        Type::List(element, true) => format!("Vec<Option<{}>>", type_name(element)),
+        Type::List(element, false) => format!("Vec<{}>", type_name(element)),
+        Type::Model(name) | Type::Enum(name) | Type::Named(name) => name.clone(),
+    }
+}
+
+/// The expression of a `d@` default, only scalars and enums have one.
+fn default_value(field: &Field) -> Option<String> {
+    let value = match (field.default.as_ref()?, &field.ty) {
+        (_, ty) if !ty.takes_default() => return None,
+        (Value::Null, _) => return None,
+        (value, Type::Enum(name)) => format!("{}::{}", name, member(value)),
+        (Value::String(value), _) => format!("String::from({})", literal(value)),
+        (Value::Number(number), Type::Double | Type::Num) if number.is_i64() => {
+            format!("{}.0", number)
+        }
+        (value, _) => value.to_string(),
+    };
+    if field.nullable {
+        Some(format!("Some({})", value))
+    } else {
+        Some(value)
+    }
+}
+
+/// Values no member matches are read as `Unknown`, like the dart enums.
+fn enumeration(item: &Enum) -> String {
+    if item.has_values() {
+        return value_enumeration(item);
+    }
+    let mut result = format!(
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]\npub enum {} {{\n",
+        item.name
+    );
+    for value in &item.values {
+        let wire = match value {
+            Value::String(value) => value.clone(),
+            _ => value.to_string(),
+        };
+        let name = member(value);
+        if name != wire {
+            result.push_str(&format!("    #[serde(rename = {})]\n", literal(&wire)));
+        }
+        result.push_str(&format!("    {},\n", name));
+    }
+    if !item.values.iter().any(|e| member(e) == "Unknown") {
+        result.push_str("    #[serde(other)]\n    Unknown,\n");
+    }
+    result.push_str("}\n");
+    result
+}
+
+/// Hand written serde impls, a rename can only match a string. Values no
+/// member equals are read as `Unknown` too.
+fn value_enumeration(item: &Enum) -> String {
+    let name = &item.name;
+    let unknown = !item.values.iter().any(|e| member(e) == "Unknown");
+    let mut result = format!(
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq)]\npub enum {} {{\n",
+        name
+    );
+    for value in &item.values {
+        result.push_str(&format!("    {},\n", member(value)));
+    }
+    if unknown {
+        result.push_str("    Unknown,\n");
+    }
+    result.push_str("}\n");
+
+    result.push_str(&format!(
+        "\nimpl Serialize for {} {{\n    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{\n        let value = match self {{\n",
+        name
+    ));
+    for value in &item.values {
+        result.push_str(&format!(
+            "            {}::{} => serde_json::json!({}),\n",
+            name,
+            member(value),
+            value
+        ));
+    }
+    if unknown {
+        result.push_str(&format!(
+            "            {}::Unknown => serde_json::Value::Null,\n",
+            name
+        ));
+    }
+    result.push_str("        };\n        value.serialize(serializer)\n    }\n}\n");
+
+    result.push_str(&format!(
+        "\nimpl<'de> Deserialize<'de> for {} {{\n    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{\n        let value = serde_json::Value::deserialize(deserializer)?;\n        let members = [\n",
+        name
+    ));
+    for value in &item.values {
+        result.push_str(&format!(
+            "            (serde_json::json!({}), {}::{}),\n",
+            value,
+            name,
+            member(value)
+        ));
+    }
+    result.push_str(&format!(
+        "        ];\n        let found = members.into_iter().find(|e| e.0 == value);\n        Ok(found.map_or({}::Unknown, |e| e.1))\n    }}\n}}\n",
+        name
+    ));
+    result
+}
+
+/// The UpperCamelCase member of an enum wire value.
+fn member(value: &Value) -> String {
+    utils::type_name(&utils::enum_member(value))
+}
+
+/// A name that is a keyword, `self` and the like can't be raw and get a `_`.
+fn identifier(name: &str) -> String {
+    if ["crate", "self", "super"].contains(&name) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn literal(value: &str) -> String {
+    model::string_literal(value, '"', &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+    use crate::utils::{DartInfo, FileInfo};
+
+    // The checked in output is compiled along with the tests.
+    #[allow(dead_code, clippy::four_forward_slashes)]
+    mod order {
+        include!("../tests/rust/order.rs");
+    }
+
+    #[allow(dead_code, clippy::four_forward_slashes)]
+    mod level {
+        include!("../tests/rust/level.rs");
+    }
+
+    fn render_info(info: &DartInfo) -> String {
+        let model = Model::from_dart(info);
+        let exports = exports(std::slice::from_ref(&model));
+        render(&model, &HashMap::new(), &exports).1
+    }
+
+    #[test]
+    fn renders_defaults() {
+        let file = FileInfo {
+            name: String::from("order"),
+            path: String::from("tests/rust/order.json"),
+            dir: String::new(),
+            content: String::from(include_str!("../tests/rust/order.json")),
+        };
+        let info = utils::parse_to_dart(&file).unwrap();
+        assert_eq!(render_info(&info), include_str!("../tests/rust/order.rs"));
+    }
+
+    #[test]
+    fn reads_missing_fields_as_defaults() {
+        let order: order::OrderModel = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        assert_eq!(order.count, 3);
+        assert_eq!(order.label, "a\"b");
+        assert_eq!(order.status, order::OrderStatus::On);
+        assert!(order.addr.is_none());
+        assert!(order.tags.is_empty());
+        assert!(order.extra.is_null());
+    }
+
+    #[test]
+    fn renders_value_enums() {
+        let schema = serde_json::from_str(include_str!("../tests/rust/level.json")).unwrap();
+        let docs = HashMap::from([(String::from("level"), schema)]);
+        let info = schema::parse_documents(&docs, &HashMap::new()).remove(0);
+        assert_eq!(render_info(&info), include_str!("../tests/rust/level.rs"));
+    }
+
+    #[test]
+    fn keeps_json_type_of_enum_values() {
+        let payload = serde_json::json!({"level": 2, "mixed": 1.5, "state": "b"});
+        let value: level::LevelModel = serde_json::from_value(payload.clone()).unwrap();
+        assert_eq!(value.level, level::LevelLevel::K2);
+        assert_eq!(value.mixed, Some(level::LevelMixed::K15));
+        assert_eq!(serde_json::to_value(&value).unwrap(), payload);
+
+        let value: level::LevelModel = serde_json::from_str(r#"{"level": "2"}"#).unwrap();
+        assert_eq!(value.level, level::LevelLevel::Unknown);
+    }
+}
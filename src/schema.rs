@@ -0,0 +1,536 @@
+use convert_case::Casing;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::model::Type;
+use crate::utils::{
+    self, DartInfo, EnumInfo, FieldInfo, FileInfo, ModelOptions, UnionInfo, VariantInfo,
+};
+
+/// All loaded schema documents keyed by file stem, `doc` is the one that
+/// relative `$ref`s are resolved against.
+struct Context<'a> {
+    docs: &'a HashMap<String, Value>,
+    doc: &'a str,
+}
+
+pub fn parse_schemas(files: &[FileInfo]) -> Result<Vec<DartInfo>, String> {
+    let docs: HashMap<String, Value> = files
+        .iter()
+        .map(|file| {
+            let doc = serde_json::from_str(&file.content)
+                .map_err(|e| format!("invalid schema {}: {}", file.path, e))?;
+            Ok((file.name.clone(), doc))
+        })
+        .collect::<Result<_, String>>()?;
+    let dirs: HashMap<String, String> = files
+        .iter()
+        .map(|file| (file.name.clone(), file.dir.clone()))
+        .collect();
+
+    Ok(parse_documents(&docs, &dirs))
+}
+
+/// Turn every document and its `$defs` into models, one file each, placed
+/// in the dir `dirs` holds for the document.
+pub fn parse_documents(
+    docs: &HashMap<String, Value>,
+    dirs: &HashMap<String, String>,
+) -> Vec<DartInfo> {
+    let mut names: Vec<&String> = docs.keys().collect();
+    names.sort();
+
+    let mut result = Vec::new();
+    for name in names {
+        let ctx = Context { docs, doc: name };
+        let doc = &docs[name];
+        let dir = dirs.get(name).map(|e| e.as_str()).unwrap_or("");
+        if is_model(doc) || doc.get("enum").is_some() {
+            let mut info = parse_schema(&ctx, name, doc);
+            info.set_dir(dir);
+            result.push(info);
+        } else if ["type", "items", "$ref", "anyOf"]
+            .iter()
+            .any(|e| doc.get(e).is_some())
+        {
+            warn_inlined(name);
+        }
+        for (def_name, def) in definitions(doc) {
+            if is_model(def) || def.get("enum").is_some() {
+                let mut info = parse_schema(&ctx, &def_file(name, def_name), def);
+                info.set_dir(dir);
+                result.push(info);
+            } else {
+                warn_inlined(&format!("{}/{}", name, def_name));
+            }
+        }
+    }
+    result
+}
+
+/// A schema that is neither an object nor an enum gets no model, it is
+/// resolved where a `$ref` points at it.
+fn warn_inlined(name: &str) {
+    println!(
+        "\t  warning: `{}` is neither an object nor an enum, it is inlined where it is referenced",
+        name
+    );
+}
+
+/// Schemas that become a class of their own, anything else is inlined where it is used.
+fn is_model(schema: &Value) -> bool {
+    schema.get("properties").is_some()
+        || schema.get("oneOf").is_some()
+        || schema.get("allOf").is_some()
+}
+
+fn definitions(doc: &Value) -> Vec<(&String, &Value)> {
+    ["$defs", "definitions"]
+        .iter()
+        .filter_map(|key| doc.get(*key).and_then(|e| e.as_object()))
+        .flat_map(|defs| defs.iter())
+        .collect()
+}
+
+fn def_file(doc: &str, def_name: &str) -> String {
+    format!("{}_{}", doc, def_name.to_case(convert_case::Case::Snake))
+}
+
+fn parse_schema(ctx: &Context, name: &str, schema: &Value) -> DartInfo {
+    let mut info = DartInfo {
+        name: name.to_string(),
+        dir: String::new(),
+        imports: Vec::new(),
+        fields: Vec::new(),
+        children: Vec::new(),
+        enums: Vec::new(),
+        union: None,
+        options: schema
+            .get("x-xtools")
+            .map(ModelOptions::from_json)
+            .unwrap_or_default(),
+    };
+
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        info.enums.push(EnumInfo {
+            name: utils::type_name(name),
+            values: values.iter().filter(|e| !e.is_null()).cloned().collect(),
+        });
+        return info;
+    }
+
+    if let Some(variants) = schema.get("oneOf").and_then(|e| e.as_array()) {
+        parse_union(ctx, &mut info, schema, variants);
+        return info;
+    }
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    collect_object(ctx, schema, &mut properties, &mut required);
+
+    for (key, property) in &properties {
+        let field_name = utils::unique_field_name(&info.fields, name, key);
+        let child_name = format!("{}_{}", name, field_name.to_case(convert_case::Case::Snake));
+        let (ty, nullable) = schema_type(ctx, &mut info, &child_name, property);
+        // like `d@` of the samples, only scalars and enums are given a default, null is none
+        let default = property
+            .get("default")
+            .or_else(|| property.get("const"))
+            .filter(|e| !e.is_null() && ty.takes_default());
+
+        info.fields.push(FieldInfo {
+            name: field_name,
+            key: key.clone(),
+            ty,
+            value: default.unwrap_or(&Value::Null).to_string(),
+            required: required.contains(key),
+            default: default.is_some(),
+            nullable,
+        });
+    }
+
+    info
+}
+
+/// Merge `properties` and `required` of the schema and everything it pulls in with `allOf`.
+fn collect_object(
+    ctx: &Context,
+    schema: &Value,
+    properties: &mut Map<String, Value>,
+    required: &mut Vec<String>,
+) {
+    if let Some(props) = schema.get("properties").and_then(|e| e.as_object()) {
+        for (key, value) in props {
+            properties.insert(key.clone(), value.clone());
+        }
+    }
+    if let Some(keys) = schema.get("required").and_then(|e| e.as_array()) {
+        required.extend(keys.iter().filter_map(|e| e.as_str()).map(String::from));
+    }
+    if let Some(parts) = schema.get("allOf").and_then(|e| e.as_array()) {
+        for part in parts {
+            match part.get("$ref").and_then(|e| e.as_str()) {
+                Some(reference) => {
+                    if let Some((doc, _, target)) = resolve_ref(ctx, reference) {
+                        let ctx = Context {
+                            docs: ctx.docs,
+                            doc: &doc,
+                        };
+                        collect_object(&ctx, target, properties, required);
+                    }
+                }
+                None => collect_object(ctx, part, properties, required),
+            }
+        }
+    }
+}
+
+/// Resolve a property schema as `(type, nullable)`, inline objects and
+/// unions become child models and inline enums live in the owner's file.
+fn schema_type(ctx: &Context, info: &mut DartInfo, name: &str, schema: &Value) -> (Type, bool) {
+    let (schema, nullable) = strip_null(schema);
+
+    if let Some(reference) = schema.get("$ref").and_then(|e| e.as_str()) {
+        return match resolve_ref(ctx, reference) {
+            Some((doc, import, target)) => {
+                if !is_model(target) && target.get("enum").is_none() {
+                    let ctx = Context {
+                        docs: ctx.docs,
+                        doc: &doc,
+                    };
+                    let (ty, inner) = schema_type(&ctx, info, name, target);
+                    return (ty, nullable || inner);
+                }
+                if import != info.name && !info.imports.contains(&import) {
+                    info.imports.push(import.clone());
+                }
+                if target.get("enum").is_some() {
+                    (Type::Enum(utils::type_name(&import)), nullable)
+                } else {
+                    (Type::Model(utils::model_name(&import)), nullable)
+                }
+            }
+            None => (Type::Dynamic, nullable),
+        };
+    }
+
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        let enum_name = utils::type_name(name);
+        info.enums.push(EnumInfo {
+            name: enum_name.clone(),
+            values: values.iter().filter(|e| !e.is_null()).cloned().collect(),
+        });
+        return (Type::Enum(enum_name), nullable);
+    }
+
+    let types = match schema.get("type") {
+        Some(Value::String(types)) => types.as_str(),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|e| e.as_str())
+            .find(|e| *e != "null")
+            .unwrap_or(""),
+        _ => "",
+    };
+    let ty = match types {
+        "string" => match schema.get("format").and_then(|e| e.as_str()) {
+            Some("date-time") | Some("date") => Type::DateTime { millis: false },
+            Some("uri") | Some("url") => Type::Uri,
+            _ => Type::String,
+        },
+        "integer" => Type::Int,
+        "number" => Type::Num,
+        "boolean" => Type::Bool,
+        "array" => {
+            let items = schema.get("items").unwrap_or(&Value::Null);
+            let element = match schema_type(ctx, info, name, items) {
+                (Type::List(..), _) => {
+                    println!(
+                        "\t  warning: `{}` is a nested list, fallback to List<dynamic>",
+                        name
+                    );
+                    Type::List(Box::new(Type::Dynamic), false)
+                }
+                (Type::Dynamic, _) => Type::List(Box::new(Type::Dynamic), false),
+                (ty, nullable) => Type::List(Box::new(ty), nullable),
+            };
+            return (element, nullable);
+        }
+        _ if is_model(schema) => {
+            let child = parse_schema(ctx, name, schema);
+            info.imports.push(child.name.clone());
+            info.children.push(child);
+            Type::Model(utils::model_name(name))
+        }
+        _ => match schema.get("const") {
+            Some(Value::String(_)) => Type::String,
+            Some(Value::Number(value)) if value.is_i64() => Type::Int,
+            Some(Value::Number(_)) => Type::Num,
+            Some(Value::Bool(_)) => Type::Bool,
+            _ => Type::Dynamic,
+        },
+    };
+
+    (ty, nullable)
+}
+
+/// Drop the `null` alternatives of a schema and report whether there were any.
+fn strip_null(schema: &Value) -> (&Value, bool) {
+    let mut nullable = schema.get("nullable").and_then(|e| e.as_bool()) == Some(true);
+
+    if let Some(Value::Array(types)) = schema.get("type") {
+        nullable = nullable || types.iter().any(|e| e == "null");
+    }
+
+    for key in ["oneOf", "anyOf"] {
+        if let Some(variants) = schema.get(key).and_then(|e| e.as_array()) {
+            let rest: Vec<&Value> = variants.iter().filter(|e| !is_null_schema(e)).collect();
+            if rest.len() < variants.len() {
+                nullable = true;
+            }
+            if rest.len() == 1 {
+                return (rest[0], nullable);
+            }
+        }
+    }
+
+    (schema, nullable)
+}
+
+fn is_null_schema(schema: &Value) -> bool {
+    schema.get("type").and_then(|e| e.as_str()) == Some("null")
+}
+
+/// Find the document, model file name and schema a `$ref` points to.
+fn resolve_ref<'a>(ctx: &Context<'a>, reference: &str) -> Option<(String, String, &'a Value)> {
+    let (file, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+    let doc_name = if file.is_empty() {
+        ctx.doc.to_string()
+    } else {
+        Path::new(file)
+            .file_stem()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    let doc = match ctx.docs.get(&doc_name) {
+        Some(doc) => doc,
+        None => {
+            println!("\t  warning: unresolved $ref `{}`", reference);
+            return None;
+        }
+    };
+    if pointer.is_empty() || pointer == "/" {
+        return Some((doc_name.clone(), doc_name, doc));
+    }
+
+    let segments: Vec<&str> = pointer.trim_start_matches('/').split('/').collect();
+    match segments[..] {
+        [defs, def_name] if defs == "$defs" || defs == "definitions" => {
+            let target = doc.pointer(pointer)?;
+            Some((doc_name.clone(), def_file(&doc_name, def_name), target))
+        }
+        _ => {
+            println!("\t  warning: unsupported $ref `{}`", reference);
+            None
+        }
+    }
+}
+
+fn parse_union(ctx: &Context, info: &mut DartInfo, schema: &Value, variants: &[Value]) {
+    let mapping = schema
+        .pointer("/discriminator/mapping")
+        .and_then(|e| e.as_object());
+    let mut key = schema
+        .pointer("/discriminator/propertyName")
+        .and_then(|e| e.as_str())
+        .map(String::from);
+
+    let mut union = UnionInfo {
+        key: None,
+        variants: Vec::new(),
+        fallback: None,
+    };
+    let mut tags = Vec::new();
+
+    for (idx, variant) in variants.iter().enumerate() {
+        let reference = variant.get("$ref").and_then(|e| e.as_str());
+        let (doc, suffix, target) = match reference.and_then(|e| resolve_ref(ctx, e)) {
+            Some((doc, import, target)) => {
+                let suffix = import.trim_start_matches(&format!("{}_", doc)).to_string();
+                (doc, suffix, target)
+            }
+            None => {
+                let suffix = variant
+                    .get("title")
+                    .and_then(|e| e.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("variant{}", idx + 1));
+                (ctx.doc.to_string(), suffix, variant)
+            }
+        };
+
+        let ctx = Context {
+            docs: ctx.docs,
+            doc: &doc,
+        };
+        let variant_name = format!(
+            "{}_{}",
+            info.name,
+            suffix.to_case(convert_case::Case::Snake)
+        );
+        let mut variant_info = parse_schema(&ctx, &variant_name, target);
+
+        for import in variant_info.imports.drain(..) {
+            if import != info.name && !info.imports.contains(&import) {
+                info.imports.push(import);
+            }
+        }
+        info.children.append(&mut variant_info.children);
+
+        let mapped = mapping.and_then(|mapping| {
+            mapping
+                .iter()
+                .find(|(_, value)| Some(value.as_str().unwrap_or("")) == reference)
+                .map(|(tag, _)| Value::String(tag.clone()))
+        });
+        tags.push((mapped, const_properties(&ctx, target), suffix));
+        union.variants.push(VariantInfo {
+            tag: None,
+            info: variant_info,
+            source: None,
+        });
+    }
+
+    if key.is_none() {
+        key = tags
+            .first()
+            .and_then(|(_, consts, _)| {
+                consts
+                    .keys()
+                    .find(|key| tags.iter().all(|(_, consts, _)| consts.contains_key(*key)))
+            })
+            .cloned();
+    }
+
+    if let Some(key) = &key {
+        for (variant, (mapped, consts, suffix)) in union.variants.iter_mut().zip(tags) {
+            variant.tag = Some(
+                mapped
+                    .or_else(|| consts.get(key).cloned())
+                    .unwrap_or(Value::String(suffix)),
+            );
+        }
+    }
+
+    union.key = key;
+    info.union = Some(union);
+}
+
+/// Properties pinned to a single value with `const` or a one-item `enum`.
+fn const_properties(ctx: &Context, schema: &Value) -> HashMap<String, Value> {
+    let mut properties = Map::new();
+    collect_object(ctx, schema, &mut properties, &mut Vec::new());
+
+    properties
+        .iter()
+        .filter_map(|(key, property)| {
+            let value = match property.get("enum").and_then(|e| e.as_array()) {
+                Some(values) if values.len() == 1 => Some(&values[0]),
+                _ => property.get("const"),
+            };
+            value.map(|value| (key.clone(), value.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(schema: Value) -> DartInfo {
+        let docs = HashMap::from([(String::from("user"), schema)]);
+        parse_documents(&docs, &HashMap::new()).remove(0)
+    }
+
+    fn has_default(info: &DartInfo, name: &str) -> bool {
+        info.fields.iter().find(|e| e.name == name).unwrap().default
+    }
+
+    #[test]
+    fn keeps_default_of_scalar_and_enum() {
+        let info = parse(json!({
+            "type": "object",
+            "properties": {
+                "age": {"type": "integer", "default": 18},
+                "role": {"enum": ["admin", "guest"], "default": "guest"}
+            }
+        }));
+        assert!(has_default(&info, "age"));
+        assert!(has_default(&info, "role"));
+    }
+
+    #[test]
+    fn drops_default_of_converted_type() {
+        let info = parse(json!({
+            "type": "object",
+            "properties": {
+                "created": {"type": "string", "format": "date-time", "default": "2024-01-01T00:00:00Z"},
+                "home": {"type": "string", "format": "uri", "default": "https://example.com"}
+            }
+        }));
+        assert!(!has_default(&info, "created"));
+        assert!(!has_default(&info, "home"));
+    }
+
+    #[test]
+    fn drops_default_of_object() {
+        let info = parse(json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "default": {}
+                }
+            }
+        }));
+        assert!(!has_default(&info, "address"));
+    }
+
+    #[test]
+    fn drops_default_of_array() {
+        let info = parse(json!({
+            "type": "object",
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "string"}, "default": ["a"]}
+            }
+        }));
+        assert!(!has_default(&info, "tags"));
+    }
+
+    #[test]
+    fn drops_null_default() {
+        let info = parse(json!({
+            "type": "object",
+            "properties": {"nickname": {"type": "string", "default": null}}
+        }));
+        assert!(!has_default(&info, "nickname"));
+    }
+
+    #[test]
+    fn escapes_default_in_dart() {
+        let info = parse(json!({
+            "type": "object",
+            "properties": {"price": {"type": "string", "default": "a$b"}}
+        }));
+        assert_eq!(utils::generate_ctor(&info.fields), "this.price = 'a\\$b',");
+        assert_eq!(
+            utils::generate_from_json(&info.fields),
+            "price: json['price'] as String? ?? 'a\\$b',\n"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use serde_json::Value;
+
+use crate::model::{self, Enum, Field, Model, Type};
+use crate::tmpl;
+use crate::utils;
+
+/// Keywords swift only accepts as a name in backticks.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "associatedtype",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "defer",
+    "deinit",
+    "do",
+    "else",
+    "enum",
+    "extension",
+    "fallthrough",
+    "false",
+    "fileprivate",
+    "for",
+    "func",
+    "guard",
+    "if",
+    "import",
+    "in",
+    "init",
+    "inout",
+    "internal",
+    "is",
+    "let",
+    "nil",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "repeat",
+    "rethrows",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "subscript",
+    "super",
+    "switch",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "typealias",
+    "var",
+    "where",
+    "while",
+];
+
+/// The file holding `JSONValue`, the type of fields without a known type.
+pub fn json_value_file() -> (String, String) {
+    (
+        String::from("JSONValue.swift"),
+        model::header() + "\n" + tmpl::SWIFT_JSON_VALUE,
+    )
+}
+
+/// Whether the model or one of its children needs `json_value_file`.
+pub fn needs_json_value(model: &Model) -> bool {
+    model
+        .flatten()
+        .iter()
+        .flat_map(|e| &e.fields)
+        .any(|e| e.ty.is_dynamic())
+}
+
+/// The `.swift` file of the model, holding the structs of the model and its
+/// children with all their enums. The path is relative to the output dir.
+pub fn render(model: &Model) -> (String, String) {
+    let mut source = model::header() + "\nimport Foundation\n";
+    for model in model.flatten() {
+        if model.has_class() {
+            source.push('\n');
+            source.push_str(&structure(model));
+        }
+        for item in &model.enums {
+            source.push('\n');
+            source.push_str(&enumeration(item));
+        }
+    }
+
+    let file = utils::join_dir(&model.dir, &format!("{}.swift", model.class_name));
+    (file, source)
+}
+
+/// A struct decoding `d@` defaults and missing lists in an `init(from:)`
+/// declared in an extension, which keeps the memberwise initializer.
+fn structure(model: &Model) -> String {
+    if model.fields.is_empty() {
+        return format!("struct {}: Codable {{}}\n", model.class_name);
+    }
+
+    let mut result = format!("struct {}: Codable {{\n", model.class_name);
+    for field in &model.fields {
+        result.push_str(&format!(
+            "    let {}: {}\n",
+            identifier(&field.name),
+            swift_type(field)
+        ));
+    }
+
+    let decoded = model.fields.iter().any(has_default);
+    if decoded || model.fields.iter().any(|e| e.key != e.name) {
+        result.push_str("\n    enum CodingKeys: String, CodingKey {\n");
+        for field in &model.fields {
+            let name = identifier(&field.name);
+            if field.key == field.name {
+                result.push_str(&format!("        case {}\n", name));
+            } else {
+                result.push_str(&format!(
+                    "        case {} = {}\n",
+                    name,
+                    literal(&field.key)
+                ));
+            }
+        }
+        result.push_str("    }\n");
+    }
+    result.push_str("}\n");
+
+    if decoded {
+        result.push_str(&format!(
+            "\nextension {} {{\n    init(from decoder: Decoder) throws {{\n",
+            model.class_name
+        ));
+        result
+            .push_str("        let container = try decoder.container(keyedBy: CodingKeys.self)\n");
+        for field in &model.fields {
+            result.push_str(&format!("        {}\n", decode(field)));
+        }
+        result.push_str("    }\n}\n");
+    }
+    result
+}
+
+fn has_default(field: &Field) -> bool {
+    default_value(field).is_some()
+}
+
+fn decode(field: &Field) -> String {
+    let name = identifier(&field.name);
+    let key = format!(".{}", name);
+    let ty = type_name(&field.ty);
+    if let Some(value) = default_value(field) {
+        format!(
+            "{} = try container.decodeIfPresent({}.self, forKey: {}) ?? {}",
+            name, ty, key, value
+        )
+    } else if field.nullable && field.required {
+        format!(
+            "{} = try container.decode({}?.self, forKey: {})",
+            name, ty, key
+        )
+    } else if field.nullable {
+        format!(
+            "{} = try container.decodeIfPresent({}.self, forKey: {})",
+            name, ty, key
+        )
+    } else {
+        format!(
+            "{} = try container.decode({}.self, forKey: {})",
+            name, ty, key
+        )
+    }
+}
+
+/// Lists default to empty, only scalars and enums have a `d@` default.
+fn default_value(field: &Field) -> Option<String> {
+    if let Type::List(..) = field.ty {
+        return Some(String::from("[]"));
+    }
+    match (field.default.as_ref()?, &field.ty) {
+        (_, ty) if !ty.takes_default() => None,
+        (Value::Null, _) => None,
+        (value, Type::Enum(_)) => Some(format!(".{}", member(value))),
+        (Value::String(value), _) => Some(literal(value)),
+        (value, _) => Some(value.to_string()),
+    }
+}
+
+fn swift_type(field: &Field) -> String {
+    let name = type_name(&field.ty);
+    if field.nullable {
+        format!("{}?", name)
+    } else {
+        name
+    }
+}
+
+/// Date times keep their json type, the date strategy is up to the decoder.
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::String | Type::DateTime { millis: false } => String::from("String"),
+        Type::Int | Type::Duration | Type::DateTime { millis: true } => String::from("Int"),
+        Type::Double | Type::Num => String::from("Double"),
+        Type::Bool => String::from("Bool"),
+        Type::Uri => String::from("URL"),
+        Type::Dynamic => String::from("JSONValue"),
+        Type::List(element, true) => format!("[{}?]", type_name(element)),
+        Type::List(element, false) => format!("[{}]", type_name(element)),
+        Type::Model(name) | Type::Enum(name) | Type::Named(name) => name.clone(),
+    }
+}
+
+/// A raw value enum, backed by `Int` when every value is an integer.
+fn enumeration(item: &Enum) -> String {
+    let raw = if !item.values.is_empty() && item.values.iter().all(|e| e.is_i64()) {
+        "Int"
+    } else if item.has_values() {
+        return value_enumeration(item);
+    } else {
+        "String"
+    };
+    let mut result = format!("enum {}: {}, Codable {{\n", item.name, raw);
+    for value in &item.values {
+        let name = member(value);
+        match value {
+            Value::String(wire) if *wire == name => {
+                result.push_str(&format!("    case {}\n", identifier(&name)));
+            }
+            Value::String(wire) => {
+                result.push_str(&format!(
+                    "    case {} = {}\n",
+                    identifier(&name),
+                    literal(wire)
+                ));
+            }
+            _ if raw == "Int" => {
+                result.push_str(&format!("    case {} = {}\n", identifier(&name), value));
+            }
+            _ => {
+                let wire = literal(&value.to_string());
+                result.push_str(&format!("    case {} = {}\n", identifier(&name), wire));
+            }
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+/// Values of different json types have no raw type in common, `Codable` is
+/// written out trying the type of every case in turn.
+fn value_enumeration(item: &Enum) -> String {
+    let mut cases = String::new();
+    let mut decode = String::new();
+    let mut encode = String::new();
+    for value in &item.values {
+        let name = identifier(&member(value));
+        let (ty, wire) = match value {
+            Value::String(wire) => ("String", literal(wire)),
+            Value::Bool(_) => ("Bool", value.to_string()),
+            Value::Number(number) if number.is_f64() => ("Double", value.to_string()),
+            _ => ("Int", value.to_string()),
+        };
+        cases.push_str(&format!("    case {}\n", name));
+        decode.push_str(&format!(
+            "if (try? container.decode({}.self)) == {} {{\n            self = .{}\n        }} else ",
+            ty, wire, name
+        ));
+        encode.push_str(&format!(
+            "        case .{}: try container.encode({} as {})\n",
+            name, wire, ty
+        ));
+    }
+    format!(
+        r#"enum {name}: Codable {{
+{cases}
+    init(from decoder: Decoder) throws {{
+        let container = try decoder.singleValueContainer()
+        {decode}{{
+            throw DecodingError.dataCorruptedError(
+                in: container, debugDescription: "unknown {name}")
+        }}
+    }}
+
+    func encode(to encoder: Encoder) throws {{
+        var container = encoder.singleValueContainer()
+        switch self {{
+{encode}        }}
+    }}
+}}
+"#,
+        name = item.name
+    )
+}
+
+/// The lowerCamelCase member of an enum wire value, without the escapes dart needs.
+fn member(value: &Value) -> String {
+    let name = utils::enum_member(value);
+    match name.strip_suffix('_') {
+        Some(plain) if !plain.is_empty() => plain.to_string(),
+        _ => name,
+    }
+}
+
+fn identifier(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn literal(value: &str) -> String {
+    model::string_literal(value, '"', &[])
+}
@@ -1,5 +1,10 @@
-#[allow(dead_code)]
-pub static DART_TMPL: &str = r#"
+use minijinja::{AutoEscape, Environment, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+macro_rules! header {
+    () => {
+        r#"
 ////////////////////////////////////////////////////////////
 ////           ____             __ 
 ////          / __ \____ ______/ /_
@@ -10,25 +15,266 @@ pub static DART_TMPL: &str = r#"
 ////   GENERATED CODE - DO NOT MODIFY BY HAND
 //// 
 ////////////////////////////////////////////////////////////
-library;
+"#
+    };
+}
+
+pub const HEADER: &str = header!();
+
+/// Where a project keeps its own templates, relative to its root.
+const TEMPLATE_DIR: &str = ".xtools/templates";
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("header", HEADER),
+    ("model", DART_TMPL),
+    ("json_serializable", JSON_SERIALIZABLE_TMPL),
+    ("freezed", FREEZED_TMPL),
+    ("sealed", SEALED_TMPL),
+    ("variant", VARIANT_TMPL),
+    ("unknown", UNKNOWN_TMPL),
+    ("enum", ENUM_TMPL),
+];
+
+/// The jinja templates dart models are rendered with. Every built-in one is
+/// replaced by a `{name}.jinja` file in `TEMPLATE_DIR`, any other file there
+/// can be included or imported by them.
+///
+/// Classes get `model`, the parsed `DartInfo`, `fields` with their
+/// `dart_type`, `options` and the rendered `class_name`, `imports`,
+/// `declarations`, `ctor`, `from_json`, `to_json`, `extras`, `params`,
+/// `converters`, `part` and `base_name` of a variant. The `unknown` variant
+/// of a union gets `class_name`, `base_name` and `key`. Enums get `enum` and
+/// its rendered `members`.
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Templates {
+    /// The built-in templates with the ones of the project at `root` in their place.
+    pub fn load(root: &Path) -> Templates {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.set_auto_escape_callback(|_| AutoEscape::None);
+        for (name, source) in BUILTIN {
+            env.add_template(name, source)
+                .unwrap_or_else(|err| panic!("invalid template `{}`: {}", name, err));
+        }
+
+        let mut files: Vec<PathBuf> = match fs::read_dir(root.join(TEMPLATE_DIR)) {
+            Ok(entries) => entries.filter_map(|e| e.ok().map(|e| e.path())).collect(),
+            Err(_) => Vec::new(),
+        };
+        files.retain(|e| e.extension().is_some_and(|ext| ext == "jinja"));
+        files.sort();
+        for file in files {
+            let name = file
+                .file_stem()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let added = fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|source| {
+                    env.add_template_owned(name.clone(), source)
+                        .map_err(|e| e.to_string())
+                });
+            match added {
+                Ok(_) => println!("template: {}", file.display()),
+                Err(err) => {
+                    // a template failing to compile is dropped, keep the built-in one
+                    println!("template {} ignored: {}", file.display(), err);
+                    if let Some((name, source)) = BUILTIN.iter().find(|e| e.0 == name) {
+                        let _ = env.add_template(name, source);
+                    }
+                }
+            }
+        }
+        Templates { env }
+    }
+
+    /// A project template can fail to render, e.g. on an undefined filter,
+    /// which is returned for the build to report.
+    pub fn render(&self, name: &str, context: Value) -> Result<String, String> {
+        self.env
+            .get_template(name)
+            .and_then(|e| e.render(context))
+            .map_err(|err| format!("render template `{}` failed: {:#}", name, err))
+    }
+}
+
+const DART_TMPL: &str = r#"{% include "header" %}library;
+
+{{ imports }}
+
+class {{ class_name }} {
+  {{ declarations }}
+
+  {{ class_name }}({% if ctor %}{ {{- ctor }}}{% endif %});
+
+  factory {{ class_name }}.fromJson(Map<String, dynamic> json) {
+    return {{ class_name }}(
+      {{ from_json }}
+    );
+  }
+
+  Map<String, dynamic> toJson() {
+    return {
+      {{ to_json }}
+    };
+  }
+
+  {{ extras }}
+}
+"#;
+
+const JSON_SERIALIZABLE_TMPL: &str = r#"{% include "header" %}library;
+
+import 'package:json_annotation/json_annotation.dart';
+{{ imports }}
+
+part '{{ part }}.g.dart';
+
+@JsonSerializable(explicitToJson: true)
+class {{ class_name }} {
+  {{ declarations }}
+
+  {{ class_name }}({% if ctor %}{ {{- ctor }}}{% endif %});
+
+  factory {{ class_name }}.fromJson(Map<String, dynamic> json) =>
+      _${{ class_name }}FromJson(json);
+
+  Map<String, dynamic> toJson() => _${{ class_name }}ToJson(this);
 
-{imports}
+  {{ extras }}
+}
+{{ converters }}"#;
+
+const FREEZED_TMPL: &str = r#"{% include "header" %}library;
+
+import 'package:freezed_annotation/freezed_annotation.dart';
+{{ imports }}
 
-class {className} {
-  {fields}
+part '{{ part }}.freezed.dart';
+part '{{ part }}.g.dart';
 
-  {className}({{ctor}});
+@freezed
+abstract class {{ class_name }} with _${{ class_name }} {
+  @JsonSerializable(explicitToJson: true)
+  const factory {{ class_name }}({{ params }}) = _{{ class_name }};
+
+  factory {{ class_name }}.fromJson(Map<String, dynamic> json) =>
+      _${{ class_name }}FromJson(json);
+}
+{{ converters }}"#;
 
-  factory {className}.fromJson(Map<String, dynamic> json) {
-    return {className}(
-      {fromJson}
+const SEALED_TMPL: &str = r#"{% include "header" %}library;
+
+{{ imports }}
+
+sealed class {{ class_name }} {
+  const {{ class_name }}();
+
+  factory {{ class_name }}.fromJson(Map<String, dynamic> json) {
+    {{ from_json }}
+  }
+
+  Map<String, dynamic> toJson();
+}
+
+{{ variants }}
+"#;
+
+const VARIANT_TMPL: &str = r#"
+class {{ class_name }} extends {{ base_name }} {
+  {{ declarations }}
+
+  {{ class_name }}({% if ctor %}{ {{- ctor }}}{% endif %});
+
+  factory {{ class_name }}.fromJson(Map<String, dynamic> json) {
+    return {{ class_name }}(
+      {{ from_json }}
     );
   }
 
+  @override
   Map<String, dynamic> toJson() {
     return {
-      {toJson}
+      {{ to_json }}
     };
   }
+
+  {{ extras }}
+}
+"#;
+
+const UNKNOWN_TMPL: &str = r#"
+class {{ class_name }} extends {{ base_name }} {
+  final Map<String, dynamic> json;
+
+  {{ class_name }}(this.json);
+
+  @override
+  Map<String, dynamic> toJson() => json;
+}
+"#;
+
+const ENUM_TMPL: &str = r#"
+enum {{ enum.name }} {
+  {{ members | join(",\n") }};
+
+  final dynamic value;
+
+  const {{ enum.name }}(this.value);
+
+  static {{ enum.name }} fromJson(dynamic json) {
+    return {{ enum.name }}.values.firstWhere(
+      (e) => e.value == json,
+      orElse: () => {{ enum.name }}.unknown,
+    );
+  }
+
+  dynamic toJson() => value;
+}
+"#;
+
+/// Written next to the swift models when a field has no known type.
+pub static SWIFT_JSON_VALUE: &str = r#"import Foundation
+
+/// Any json value.
+enum JSONValue: Codable, Equatable {
+    case string(String)
+    case number(Double)
+    case bool(Bool)
+    case array([JSONValue])
+    case object([String: JSONValue])
+    case null
+
+    init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+        } else if let value = try? container.decode(Bool.self) {
+            self = .bool(value)
+        } else if let value = try? container.decode(Double.self) {
+            self = .number(value)
+        } else if let value = try? container.decode(String.self) {
+            self = .string(value)
+        } else if let value = try? container.decode([JSONValue].self) {
+            self = .array(value)
+        } else {
+            self = .object(try container.decode([String: JSONValue].self))
+        }
+    }
+
+    func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        switch self {
+        case .string(let value): try container.encode(value)
+        case .number(let value): try container.encode(value)
+        case .bool(let value): try container.encode(value)
+        case .array(let value): try container.encode(value)
+        case .object(let value): try container.encode(value)
+        case .null: try container.encodeNil()
+        }
+    }
 }
-"#;
\ No newline at end of file
+"#;
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::model::{self, Enum, Field, Model, Type};
+use crate::utils;
+
+/// The types a model's file exports, keyed by the model name, what files
+/// importing the model import from it.
+pub fn exports(models: &[Model]) -> HashMap<String, Vec<String>> {
+    let mut result = HashMap::new();
+    for model in models.iter().flat_map(|e| e.flatten()) {
+        result.insert(model.name.clone(), model.declarations());
+    }
+    result
+}
+
+/// Render the model and each of its children into its own file, the same
+/// way the dart models are split, imports follow the dart ones.
+pub fn render(
+    model: &Model,
+    dirs: &HashMap<String, Vec<String>>,
+    exports: &HashMap<String, Vec<String>>,
+    zod: bool,
+    sources: &mut Vec<(String, String)>,
+) {
+    for child in &model.children {
+        render(child, dirs, exports, zod, sources);
+    }
+
+    let mut source = model::header() + "\n";
+    if zod {
+        source.push_str("import { z } from 'zod';\n");
+    }
+    let used: Vec<&str> = model
+        .fields
+        .iter()
+        .filter_map(|e| e.ty.used_type())
+        .collect();
+    for (name, path) in utils::resolve_imports(&model.dir, &model.imports, dirs, |e| e.to_string())
+    {
+        let mut names = Vec::new();
+        let found = exports.get(&name).into_iter().flatten();
+        for export in found.filter(|e| used.contains(&e.as_str())) {
+            if zod {
+                names.push(format!("type {}", export));
+                names.push(format!("{}Schema", export));
+            } else {
+                names.push(export.clone());
+            }
+        }
+        if names.is_empty() {
+            continue;
+        }
+        let path = if path.starts_with("..") {
+            path
+        } else {
+            format!("./{}", path)
+        };
+        let keyword = if zod { "import" } else { "import type" };
+        source.push_str(&format!(
+            "{} {{ {} }} from '{}';\n",
+            keyword,
+            names.join(", "),
+            path
+        ));
+    }
+
+    // a schema must be declared before the object schemas using it
+    for item in &model.enums {
+        source.push('\n');
+        source.push_str(&enumeration(item, zod));
+    }
+    if model.has_class() {
+        source.push('\n');
+        source.push_str(&interface(model));
+        if zod {
+            source.push('\n');
+            source.push_str(&object_schema(model));
+        }
+    }
+
+    let file = utils::join_dir(&model.dir, &format!("{}.ts", model.name));
+    sources.push((file, source));
+}
+
+/// The model or enum a field refers to.
+/// Properties are named by their json key, a field that isn't `r@` is
+/// optional and may be null unless it has a default.
+fn interface(model: &Model) -> String {
+    let mut result = format!("export interface {} {{\n", model.class_name);
+    for field in &model.fields {
+        let ty = type_name(&field.ty);
+        let line = if field.required && field.nullable && field.ty != Type::Dynamic {
+            format!("{}: {} | null", key(&field.key), ty)
+        } else if field.required {
+            format!("{}: {}", key(&field.key), ty)
+        } else if has_default(field) || field.ty == Type::Dynamic {
+            format!("{}?: {}", key(&field.key), ty)
+        } else {
+            format!("{}?: {} | null", key(&field.key), ty)
+        };
+        result.push_str(&format!("  {};\n", line));
+    }
+    result.push_str("}\n");
+    result
+}
+
+fn object_schema(model: &Model) -> String {
+    let mut result = format!(
+        "export const {name}Schema: z.ZodType<{name}> = z.object({{\n",
+        name = model.class_name
+    );
+    for field in &model.fields {
+        let mut schema = schema(&field.ty);
+        match default_value(field) {
+            Some(value) => schema.push_str(&format!(".default({})", value)),
+            None if field.ty == Type::Dynamic => {}
+            None if field.required && field.nullable => schema.push_str(".nullable()"),
+            None if !field.required => schema.push_str(".nullish()"),
+            None => {}
+        }
+        result.push_str(&format!("  {}: {},\n", key(&field.key), schema));
+    }
+    result.push_str("});\n");
+    result
+}
+
+fn has_default(field: &Field) -> bool {
+    field.default.is_some() || matches!(field.ty, Type::List(..))
+}
+
+fn default_value(field: &Field) -> Option<String> {
+    if field.required {
+        return None;
+    }
+    match (&field.default, &field.ty) {
+        (Some(value), _) => Some(literal(value)),
+        (None, Type::List(..)) => Some(String::from("[]")),
+        _ => None,
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::String | Type::Uri | Type::DateTime { millis: false } => String::from("string"),
+        Type::Int | Type::Double | Type::Num | Type::Duration | Type::DateTime { millis: true } => {
+            String::from("number")
+        }
+        Type::Bool => String::from("boolean"),
+        Type::Dynamic => String::from("unknown"),
+        Type::List(element, true) => format!("({} | null)[]", type_name(element)),
+        Type::List(element, false) => format!("{}[]", type_name(element)),
+        Type::Model(name) | Type::Enum(name) | Type::Named(name) => name.clone(),
+    }
+}
+
+/// Models are referenced lazily, they may be declared later or in a file
+/// importing this one.
+fn schema(ty: &Type) -> String {
+    match ty {
+        Type::String | Type::DateTime { millis: false } => String::from("z.string()"),
+        Type::Uri => String::from("z.string().url()"),
+        Type::Int | Type::Duration | Type::DateTime { millis: true } => {
+            String::from("z.number().int()")
+        }
+        Type::Double | Type::Num => String::from("z.number()"),
+        Type::Bool => String::from("z.boolean()"),
+        Type::Dynamic => String::from("z.unknown()"),
+        Type::List(element, true) => format!("z.array({}.nullable())", schema(element)),
+        Type::List(element, false) => format!("z.array({})", schema(element)),
+        Type::Enum(name) => format!("{}Schema", name),
+        Type::Model(name) => format!("z.lazy(() => {}Schema)", name),
+        Type::Named(name) => format!("z.custom<{}>()", name),
+    }
+}
+
+fn enumeration(item: &Enum, zod: bool) -> String {
+    let values: Vec<String> = item.values.iter().map(literal).collect();
+    let values = if values.is_empty() {
+        vec![String::from("never")]
+    } else {
+        values
+    };
+    let mut result = format!("export type {} = {};\n", item.name, values.join(" | "));
+    if !zod {
+        return result;
+    }
+
+    let schema = if item.values.is_empty() {
+        String::from("z.never()")
+    } else if item.values.iter().all(|e| e.is_string()) {
+        format!("z.enum([{}])", values.join(", "))
+    } else if item.values.len() == 1 {
+        format!("z.literal({})", values[0])
+    } else {
+        let literals: Vec<String> = values.iter().map(|e| format!("z.literal({})", e)).collect();
+        format!("z.union([{}])", literals.join(", "))
+    };
+    result.push_str(&format!(
+        "\nexport const {name}Schema: z.ZodType<{name}> = {schema};\n",
+        name = item.name,
+        schema = schema
+    ));
+    result
+}
+
+/// A property name, quoted unless it is an identifier.
+fn key(key: &str) -> String {
+    let identifier = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        literal(&Value::String(key.to_string()))
+    }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::String(value) => model::string_literal(value, '\'', &[]),
+        _ => value.to_string(),
+    }
+}
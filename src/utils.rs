@@ -1,13 +1,19 @@
 use convert_case::Casing;
 use csv::StringRecord;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use serde_json::Value;
+use minijinja::context;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::Path;
 use zip::read::ZipArchive;
 
 use crate::builder::TransItem;
+use crate::config;
+use crate::model::Type;
+use crate::tmpl::Templates;
 
 pub fn download_file(url: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
     let resp = reqwest::blocking::get(url)?;
@@ -56,17 +62,29 @@ pub(crate) fn delete_file(as_str: &str) {
     std::fs::remove_file(as_str).expect("failed to delete file");
 }
 
+/// A source file, `dir` is its sub directory below the input path joined by `/`.
 pub struct FileInfo {
     pub name: String,
+    pub path: String,
+    pub dir: String,
     pub content: String,
 }
 
 pub fn read_all_files(path: &str) -> Result<Option<Vec<FileInfo>>, std::io::Error> {
     let mut result = Vec::new();
-    for entry in fs::read_dir(Path::new(path))? {
-        let entry = entry?;
+    read_dir_files(Path::new(path), "", &mut result)?;
+    Ok(Some(result))
+}
+
+fn read_dir_files(path: &Path, dir: &str, result: &mut Vec<FileInfo>) -> io::Result<()> {
+    let mut entries = fs::read_dir(path)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
         let path = entry.path();
-        if path.is_file() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            read_dir_files(&path, &join_dir(dir, &file_name), result)?;
+        } else if path.is_file() {
             println!("\t- {}", path.display());
             let name = path
                 .file_stem()
@@ -75,79 +93,779 @@ pub fn read_all_files(path: &str) -> Result<Option<Vec<FileInfo>>, std::io::Erro
             let content = fs::read_to_string(&path)?;
             result.push(FileInfo {
                 name: String::from(name),
+                path: path.display().to_string(),
+                dir: dir.to_string(),
                 content,
             });
         }
     }
-    Ok(Some(result))
+    Ok(())
+}
+
+pub fn join_dir(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
 }
 
 pub struct FieldInfo {
     pub name: String,
-    pub types: String,
+    pub key: String,
+    pub ty: Type,
     pub value: String,
-    pub sub_type: String,
     pub required: bool,
     pub default: bool,
+    pub nullable: bool,
+}
+
+impl FieldInfo {
+    /// What the dart code is generated for: `array`, `object` and `enum`, or
+    /// else the dart type.
+    pub fn types(&self) -> String {
+        match &self.ty {
+            Type::List(..) => String::from("array"),
+            Type::Model(_) => String::from("object"),
+            Type::Enum(_) => String::from("enum"),
+            ty => dart_type_name(ty),
+        }
+    }
+
+    /// The element of a list, `?` when it may be null, the class of an
+    /// object or enum, and the json type of a converted type.
+    pub fn sub_type(&self) -> String {
+        match &self.ty {
+            Type::List(element, _) if **element == Type::Dynamic => String::from("dynamic"),
+            Type::List(element, true) => format!("{}?", dart_type_name(element)),
+            Type::List(element, false) => dart_type_name(element),
+            Type::Model(name) | Type::Enum(name) => name.clone(),
+            Type::DateTime { millis: true } | Type::Duration => String::from("int"),
+            Type::DateTime { millis: false } | Type::Uri => String::from("String"),
+            _ => String::new(),
+        }
+    }
 }
 
+/// Templates see the dart strings the type is generated with.
+impl Serialize for FieldInfo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("FieldInfo", 8)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("key", &self.key)?;
+        state.serialize_field("types", &self.types())?;
+        state.serialize_field("value", &self.value)?;
+        state.serialize_field("sub_type", &self.sub_type())?;
+        state.serialize_field("required", &self.required)?;
+        state.serialize_field("default", &self.default)?;
+        state.serialize_field("nullable", &self.nullable)?;
+        state.end()
+    }
+}
+
+/// The dart name of a type, the class of a model or enum.
+fn dart_type_name(ty: &Type) -> String {
+    match ty {
+        Type::String => String::from("String"),
+        Type::Int => String::from("int"),
+        Type::Double => String::from("double"),
+        Type::Num => String::from("num"),
+        Type::Bool => String::from("bool"),
+        Type::Dynamic => String::from("dynamic"),
+        Type::DateTime { .. } => String::from("DateTime"),
+        Type::Duration => String::from("Duration"),
+        Type::Uri => String::from("Uri"),
+        Type::List(element, true) => format!("List<{}?>", dart_type_name(element)),
+        Type::List(element, false) => format!("List<{}>", dart_type_name(element)),
+        Type::Model(name) | Type::Enum(name) | Type::Named(name) => name.clone(),
+    }
+}
+
+/// `dir` is where the model file goes below the output path, the same as
+/// the file it was parsed from.
+#[derive(Serialize)]
 pub struct DartInfo {
+    pub name: String,
+    pub dir: String,
     pub imports: Vec<String>,
     pub fields: Vec<FieldInfo>,
+    pub children: Vec<DartInfo>,
+    pub enums: Vec<EnumInfo>,
+    pub union: Option<UnionInfo>,
+    pub options: ModelOptions,
 }
 
-pub fn parse_to_dart(file: &FileInfo) -> DartInfo {
-    let parsed: Value = serde_json::from_str(&file.content).unwrap();
-    let map = parsed.as_object().unwrap();
+/// Opt-in members of a generated class, `None` leaves the choice to the
+/// enclosing file or the project.
+#[derive(Clone, Copy, Default, Debug, Serialize)]
+pub struct ModelOptions {
+    pub copy_with: Option<bool>,
+    pub equality: Option<bool>,
+    pub to_string: Option<bool>,
+}
 
-    let mut fields = Vec::new();
-    let mut imports = Vec::new();
+impl ModelOptions {
+    /// Parse a list like `copyWith,equality,toString`.
+    pub fn from_list(list: &str) -> ModelOptions {
+        let mut options = ModelOptions::default();
+        for item in list.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            options.set(item, true);
+        }
+        options
+    }
+
+    /// Parse the `"@"` key of a json sample or `x-xtools` of a schema, either
+    /// a list string or an object like `{"copyWith": true}`.
+    pub fn from_json(value: &Value) -> ModelOptions {
+        match value {
+            Value::String(list) => ModelOptions::from_list(list),
+            Value::Object(map) => {
+                let mut options = ModelOptions::default();
+                for (key, value) in map {
+                    if let Some(enabled) = value.as_bool() {
+                        options.set(key, enabled);
+                    }
+                }
+                options
+            }
+            _ => ModelOptions::default(),
+        }
+    }
+
+    fn set(&mut self, name: &str, enabled: bool) {
+        match name.to_case(convert_case::Case::Camel).as_str() {
+            "copyWith" => self.copy_with = Some(enabled),
+            "equality" | "equals" | "hashCode" => self.equality = Some(enabled),
+            "toString" => self.to_string = Some(enabled),
+            other => println!("\t  warning: unknown model option `{}`", other),
+        }
+    }
+
+    /// Options of `other` win where they are set.
+    pub fn merge(self, other: ModelOptions) -> ModelOptions {
+        ModelOptions {
+            copy_with: other.copy_with.or(self.copy_with),
+            equality: other.equality.or(self.equality),
+            to_string: other.to_string.or(self.to_string),
+        }
+    }
+}
+
+/// How a dart model reads and writes json: with the code written out, or
+/// annotated for `json_serializable` or `freezed` to generate it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Style {
+    Plain,
+    JsonSerializable,
+    Freezed,
+}
+
+impl Style {
+    pub fn from_name(name: &str) -> Option<Style> {
+        match name {
+            "plain" => Some(Style::Plain),
+            "json_serializable" | "json-serializable" => Some(Style::JsonSerializable),
+            "freezed" => Some(Style::Freezed),
+            _ => None,
+        }
+    }
+}
+
+impl DartInfo {
+    /// Place the model, its children and variants in `dir`.
+    pub fn set_dir(&mut self, dir: &str) {
+        self.dir = dir.to_string();
+        for child in &mut self.children {
+            child.set_dir(dir);
+        }
+        if let Some(union) = &mut self.union {
+            for variant in &mut union.variants {
+                variant.info.set_dir(dir);
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct EnumInfo {
+    pub name: String,
+    pub values: Vec<Value>,
+}
+
+/// A sealed class whose variants live in the same file, `key` is the
+/// discriminator field when the variants can be told apart by a tag.
+/// Json not matching any variant is read as the `fallback` model, if any.
+#[derive(Serialize)]
+pub struct UnionInfo {
+    pub key: Option<String>,
+    pub variants: Vec<VariantInfo>,
+    pub fallback: Option<String>,
+}
+
+/// `source` is the sample file the variant was read from, when it is not
+/// the file of the union.
+#[derive(Serialize)]
+pub struct VariantInfo {
+    pub tag: Option<Value>,
+    pub info: DartInfo,
+    pub source: Option<String>,
+}
+
+/// Top level key of a json sample holding its `ModelOptions`.
+const OPTIONS_KEY: &str = "@";
+
+/// Top level key of a json sample naming the discriminator of a union.
+const UNION_KEY: &str = "@union";
+
+pub fn model_name(name: &str) -> String {
+    let naming = config::naming();
+    format!(
+        "{}{}{}",
+        naming.class_prefix,
+        type_name(name),
+        naming.class_suffix
+    )
+}
+
+/// The file a model is written to.
+pub fn model_file(name: &str) -> String {
+    format!("{}{}", name, config::naming().file_suffix)
+}
+
+/// Words dart rejects or that would clash with the members every generated
+/// class or enum already has.
+const RESERVED_WORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "assert",
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "covariant",
+    "default",
+    "deferred",
+    "do",
+    "dynamic",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "extension",
+    "external",
+    "factory",
+    "false",
+    "final",
+    "finally",
+    "for",
+    "Function",
+    "get",
+    "hide",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "interface",
+    "is",
+    "late",
+    "library",
+    "mixin",
+    "new",
+    "null",
+    "on",
+    "operator",
+    "part",
+    "required",
+    "rethrow",
+    "return",
+    "set",
+    "show",
+    "static",
+    "super",
+    "switch",
+    "sync",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    "copyWith",
+    "fromJson",
+    "hashCode",
+    "index",
+    "noSuchMethod",
+    "runtimeType",
+    "toJson",
+    "toString",
+    "values",
+];
 
-    for (name, value) in map {
-        let (mut is_required, mut is_default) = (false, false);
-        let mut types = get_type(value);
-        let mut sub_type = String::new();
+/// Turn any name into a valid dart identifier: invalid characters are dropped,
+/// a leading digit gets a `k` prefix and reserved words a `_` suffix.
+pub fn dart_identifier(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    if name.is_empty() {
+        String::from("k")
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("k{}", name)
+    } else if RESERVED_WORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// The name before `dart_identifier` escaped a reserved word.
+pub fn plain_identifier(name: &str) -> &str {
+    match name.strip_suffix('_') {
+        Some(word) if RESERVED_WORDS.contains(&word) => word,
+        _ => name,
+    }
+}
+
+/// The lowerCamelCase dart field name for a json key.
+pub fn field_name(key: &str) -> String {
+    dart_identifier(&key.to_case(convert_case::Case::Camel))
+}
 
-        if types == "array" {
-            if let Some(array) = value.as_array() {
-                if let Some(first_val) = array.first() {
-                    sub_type = get_type(first_val);
+/// The field name of `key` in the model `owner`, with a number appended when
+/// an earlier key of `fields` already has it, like `user_id` and `userId`.
+pub fn unique_field_name(fields: &[FieldInfo], owner: &str, key: &str) -> String {
+    let name = field_name(key);
+    let taken = |name: &str| fields.iter().any(|e| e.name == name);
+    if !taken(&name) {
+        return name;
+    }
+    let unique = (2..)
+        .map(|idx| format!("{}{}", name, idx))
+        .find(|e| !taken(e))
+        .unwrap_or_default();
+    println!(
+        "\t  warning: `{}` and an earlier key of `{}` are both named `{}`, it is read as `{}`",
+        key, owner, name, unique
+    );
+    unique
+}
+
+/// The UpperCamelCase dart type name for a file or key name.
+pub fn type_name(name: &str) -> String {
+    let name: String = name
+        .to_case(convert_case::Case::Pascal)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("K{}", name)
+    } else {
+        name
+    }
+}
+
+pub fn parse_to_dart(file: &FileInfo) -> Result<DartInfo, String> {
+    let parsed: Value = serde_json::from_str(&file.content)
+        .map_err(|e| format!("invalid json {}: {}", file.path, e))?;
+    let map = parsed
+        .as_object()
+        .ok_or_else(|| format!("invalid json {}: not an object", file.path))?;
+
+    let mut info = match map.get(UNION_KEY) {
+        Some(key) => parse_union(file, key.as_str().unwrap_or_default(), map),
+        None => parse_object(&file.name, &[map]),
+    };
+    if let Some(options) = map.get(OPTIONS_KEY) {
+        info.options = ModelOptions::from_json(options);
+    }
+    info.set_dir(&file.dir);
+    Ok(info)
+}
+
+/// A sample like `{"@union": "type", "post": {..}, "video": "video.json"}`,
+/// every other key is a variant given by a sample, a list of samples or the
+/// path of a sample file next to this one. A variant is tagged with the
+/// value its samples have for the discriminator, or else its key.
+fn parse_union(file: &FileInfo, key: &str, map: &Map<String, Value>) -> DartInfo {
+    let mut info = parse_object(&file.name, &[]);
+    let mut union = UnionInfo {
+        key: Some(key.to_string()),
+        variants: Vec::new(),
+        fallback: Some(format!("{}_unknown", file.name)),
+    };
+    if key.is_empty() {
+        println!(
+            "\t  warning: `{}` of `{}` is not a key",
+            UNION_KEY, file.name
+        );
+        union.key = None;
+    }
+
+    for (name, value) in map.iter().filter(|(e, _)| !e.starts_with('@')) {
+        let (loaded, source) = match value {
+            Value::String(path) => {
+                let path = Path::new(&file.path).with_file_name(path);
+                let loaded = fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|e| serde_json::from_str(&e).map_err(|e| e.to_string()));
+                match loaded {
+                    Ok(loaded) => {
+                        let source = fs::canonicalize(&path).unwrap_or(path);
+                        (loaded, Some(source.display().to_string()))
+                    }
+                    Err(err) => {
+                        println!("\t  warning: variant {} ignored: {}", path.display(), err);
+                        continue;
+                    }
                 }
             }
-        } else if types == "String" {
-            if let Some(val) = value.as_str() {
-                if val.starts_with("[]") {
-                    types = String::from("array");
-                    sub_type = val.replace("[]", "");
-                    imports.push(sub_type.clone());
-                    sub_type = format!("{}Model", sub_type.to_case(convert_case::Case::Pascal));
+            value => (value.clone(), None),
+        };
+        let samples: Vec<&Map<String, Value>> = match &loaded {
+            Value::Array(items) => items.iter().filter_map(|e| e.as_object()).collect(),
+            value => value.as_object().into_iter().collect(),
+        };
+        if samples.is_empty() {
+            println!("\t  warning: variant `{}` has no sample", name);
+            continue;
+        }
+
+        let variant_name = format!("{}_{}", file.name, name.to_case(convert_case::Case::Snake));
+        let mut variant_info = parse_object(&variant_name, &samples);
+        for import in variant_info.imports.drain(..) {
+            if import != info.name && !info.imports.contains(&import) {
+                info.imports.push(import);
+            }
+        }
+        info.children.append(&mut variant_info.children);
+
+        let mut tags: Vec<&Value> = samples
+            .iter()
+            .flat_map(|e| e.iter())
+            .filter(|(raw, _)| parse_key(raw).key == key)
+            .map(|(_, value)| value)
+            .collect();
+        tags.dedup();
+        let tag = match tags.as_slice() {
+            [tag] if !tag.is_array() && !tag.is_object() && !tag.is_null() => (*tag).clone(),
+            _ => {
+                if union.key.is_some() {
+                    println!(
+                        "\t  warning: variant `{}` has no single `{}`, it is tagged `{}`",
+                        name, key, name
+                    );
                 }
+                Value::String(name.clone())
+            }
+        };
+        union.variants.push(VariantInfo {
+            tag: union.key.as_ref().map(|_| tag),
+            info: variant_info,
+            source,
+        });
+    }
+
+    if union
+        .variants
+        .iter()
+        .any(|e| union.fallback.as_ref() == Some(&e.info.name))
+    {
+        union.fallback = None;
+    }
+    info.union = Some(union);
+    info
+}
+
+/// A json sample key like `r@userName:String=user-name`: flags before the
+/// last `@`, an optional type hint after `:` and an explicit wire key after `=`.
+pub struct KeyInfo {
+    pub name: String,
+    pub key: String,
+    pub flags: Vec<String>,
+    pub hint: Option<String>,
+}
+
+pub fn parse_key(raw: &str) -> KeyInfo {
+    let (left, wire) = match raw.split_once('=') {
+        Some((left, wire)) => (left, Some(wire)),
+        None => (raw, None),
+    };
+    let (flags, rest) = match left.rsplit_once('@') {
+        Some((flags, rest)) => (flags.split('@').map(String::from).collect(), rest),
+        None => (Vec::new(), left),
+    };
+    let (name, hint) = match rest.split_once(':') {
+        Some((name, hint)) => (name, Some(hint.to_string())),
+        None => (rest, None),
+    };
+
+    KeyInfo {
+        name: name.to_string(),
+        key: wire.unwrap_or(name).to_string(),
+        flags,
+        hint,
+    }
+}
+
+/// Build one model from every sample object, a key missing from some
+/// samples makes the field nullable.
+fn parse_object(class: &str, samples: &[&Map<String, Value>]) -> DartInfo {
+    let mut fields = Vec::new();
+    let mut imports = Vec::new();
+    let mut children = Vec::new();
+    let mut enums = Vec::new();
+
+    let mut keys: Vec<&String> = Vec::new();
+    for sample in samples {
+        for key in sample.keys() {
+            if key != OPTIONS_KEY && !keys.contains(&key) {
+                keys.push(key);
             }
         }
+    }
+
+    for key in keys {
+        let values: Vec<&Value> = samples.iter().filter_map(|e| e.get(key)).collect();
+        let KeyInfo {
+            name,
+            key,
+            flags,
+            hint,
+        } = parse_key(key);
+        let is_required = flags.iter().any(|e| e == "r");
+        let is_default = !is_required && flags.iter().any(|e| e == "d");
 
-        if name.starts_with("r@") {
-            is_required = true;
-        } else if name.starts_with("d@") {
-            is_default = true;
+        let name = unique_field_name(&fields, class, &name);
+        let child_name = format!("{}_{}", class, name.to_case(convert_case::Case::Snake));
+
+        if flags.iter().any(|e| e == "e") {
+            let (ty, item) = parse_enum(&child_name, &values);
+            fields.push(FieldInfo {
+                name,
+                key,
+                default: is_default && ty.takes_default(),
+                ty,
+                value: item.values.first().unwrap_or(&Value::Null).to_string(),
+                required: is_required,
+                nullable: values.len() < samples.len(),
+            });
+            enums.push(item);
+            continue;
         }
 
-        let name = name.split('@').last().unwrap().to_string();
+        let (mut ty, nullable) = merge_type(&child_name, &values, &mut imports, &mut children);
+        let structured = matches!(ty, Type::List(..) | Type::Model(_));
+
+        let convert = ["dt", "dur", "uri"]
+            .iter()
+            .find(|flag| flags.iter().any(|e| e == *flag));
+        if let Some(flag) = convert {
+            if structured {
+                println!("\t  warning: `{}@` is ignored on `{}`", flag, child_name);
+            } else {
+                ty = match *flag {
+                    "dt" => Type::DateTime {
+                        millis: ty == Type::Int,
+                    },
+                    "dur" => Type::Duration,
+                    _ => Type::Uri,
+                };
+            }
+        }
+
+        if let Some(hint) = hint {
+            if !structured {
+                ty = hint_type(&hint);
+            }
+        } else if ty == Type::Dynamic && values.iter().all(|e| e.is_null()) {
+            println!(
+                "\t  warning: `{}` is always null, add a type hint like `{}:String`",
+                child_name, name
+            );
+        }
 
         fields.push(FieldInfo {
             name,
-            types,
-            value: value.to_string(),
-            sub_type,
+            key,
+            default: is_default && ty.takes_default(),
+            ty,
+            value: values[0].to_string(),
             required: is_required,
-            default: is_default,
+            nullable: nullable || values.len() < samples.len(),
         });
     }
 
-    DartInfo { imports, fields }
+    DartInfo {
+        name: class.to_string(),
+        dir: String::new(),
+        imports,
+        fields,
+        children,
+        enums,
+        union: None,
+        options: ModelOptions::default(),
+    }
+}
+
+/// Collect the members of an `e@` field from samples like `"active|banned"`,
+/// a list of such strings makes it a list of the enum.
+fn parse_enum(name: &str, values: &[&Value]) -> (Type, EnumInfo) {
+    let mut is_list = false;
+    let mut members: Vec<Value> = Vec::new();
+    for value in values {
+        let items: Vec<&Value> = match value {
+            Value::Array(items) => {
+                is_list = true;
+                items.iter().collect()
+            }
+            _ => vec![*value],
+        };
+        for text in items.iter().filter_map(|e| e.as_str()) {
+            for member in text.split('|').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+                let member = Value::String(member.to_string());
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+    }
+
+    let item = EnumInfo {
+        name: type_name(name),
+        values: members,
+    };
+    let ty = Type::Enum(item.name.clone());
+    if is_list {
+        (Type::List(Box::new(ty), false), item)
+    } else {
+        (ty, item)
+    }
+}
+
+/// The type of a `name:Type` hint, one xtools doesn't know is used as written.
+fn hint_type(hint: &str) -> Type {
+    match hint {
+        "String" => Type::String,
+        "int" => Type::Int,
+        "double" => Type::Double,
+        "num" => Type::Num,
+        "bool" => Type::Bool,
+        "dynamic" => Type::Dynamic,
+        "DateTime" => Type::DateTime { millis: false },
+        "Duration" => Type::Duration,
+        "Uri" => Type::Uri,
+        other => Type::Named(other.to_string()),
+    }
+}
+
+/// Resolve the type shared by all sample values of one field and whether any
+/// of them is null, nested objects become child models named after `name`.
+fn merge_type(
+    name: &str,
+    values: &[&Value],
+    imports: &mut Vec<String>,
+    children: &mut Vec<DartInfo>,
+) -> (Type, bool) {
+    let nullable = values.iter().any(|e| e.is_null());
+    let values: Vec<&Value> = values.iter().copied().filter(|e| !e.is_null()).collect();
+    if values.is_empty() {
+        return (Type::Dynamic, true);
+    }
+
+    let mut kinds: Vec<String> = Vec::new();
+    for value in &values {
+        let kind = get_type(value);
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    if kinds.len() == 2
+        && kinds.contains(&"int".to_string())
+        && kinds.contains(&"double".to_string())
+    {
+        kinds = vec![String::from("num")];
+    }
+    if kinds.len() == 2
+        && kinds.contains(&"String".to_string())
+        && kinds.contains(&"DateTime".to_string())
+    {
+        kinds = vec![String::from("String")];
+    }
+
+    if kinds.len() != 1 {
+        println!(
+            "\t  warning: `{}` has mixed types, fallback to dynamic",
+            name
+        );
+        return (Type::Dynamic, nullable);
+    }
+
+    let ty = match kinds[0].as_str() {
+        "object" => {
+            let objects: Vec<&Map<String, Value>> =
+                values.iter().filter_map(|e| e.as_object()).collect();
+            let child = parse_object(name, &objects);
+            let ty = Type::Model(model_name(&child.name));
+            imports.push(child.name.clone());
+            children.push(child);
+            ty
+        }
+        "array" => {
+            let elements: Vec<&Value> = values
+                .iter()
+                .filter_map(|e| e.as_array())
+                .flat_map(|e| e.iter())
+                .collect();
+            let element = if elements.is_empty() {
+                (Type::Dynamic, false)
+            } else {
+                merge_type(name, &elements, imports, children)
+            };
+            match element {
+                (Type::List(..), _) => {
+                    println!(
+                        "\t  warning: `{}` is a nested list, fallback to List<dynamic>",
+                        name
+                    );
+                    Type::List(Box::new(Type::Dynamic), false)
+                }
+                (Type::Dynamic, _) => Type::List(Box::new(Type::Dynamic), false),
+                (element, nullable) => Type::List(Box::new(element), nullable),
+            }
+        }
+        "String" => match values[0].as_str() {
+            Some(val) if val.starts_with("[]") => {
+                let import = val.replace("[]", "");
+                let name = import.rsplit('/').next().unwrap_or("").to_string();
+                imports.push(import);
+                Type::List(Box::new(Type::Model(model_name(&name))), false)
+            }
+            _ => Type::String,
+        },
+        "DateTime" => Type::DateTime { millis: false },
+        "int" => Type::Int,
+        "double" => Type::Double,
+        "num" => Type::Num,
+        "bool" => Type::Bool,
+        _ => Type::Dynamic,
+    };
+
+    (ty, nullable)
 }
 
 fn get_type(value: &Value) -> String {
     match value {
+        Value::String(val) if is_iso_date_time(val) => String::from("DateTime"),
         Value::String(_) => String::from("String"),
         Value::Number(_) => {
             if value.is_i64() {
@@ -157,46 +875,206 @@ fn get_type(value: &Value) -> String {
             }
         }
         Value::Array(_) => String::from("array"),
-        Value::Object(_) => String::from("dynamic"),
-        _ => String::new(),
+        Value::Object(_) => String::from("object"),
+        Value::Bool(_) => String::from("bool"),
+        Value::Null => String::from("null"),
+    }
+}
+
+/// Match `YYYY-MM-DD` with an optional `THH:MM[:SS[.fff]]` and offset.
+pub(crate) fn is_iso_date_time(val: &str) -> bool {
+    let digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let bytes = val.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return false;
+    }
+    // a multi-byte char may straddle these bounds, once they are digits the
+    // first 10 bytes are ascii and the time can be sliced
+    let part = |start: usize, end: usize| val.get(start..end).is_some_and(digits);
+    if !part(0, 4) || !part(5, 7) || !part(8, 10) {
+        return false;
+    }
+    if bytes.len() == 10 {
+        return true;
     }
+    if bytes[10] != b'T' && bytes[10] != b' ' {
+        return false;
+    }
+
+    let time = &val[11..];
+    let time = time
+        .strip_suffix('Z')
+        .or_else(|| time.rfind(['+', '-']).map(|idx| &time[..idx]))
+        .unwrap_or(time);
+    let time = time.split('.').next().unwrap_or("");
+    let parts: Vec<&str> = time.split(':').collect();
+    (parts.len() == 2 || parts.len() == 3) && parts.iter().all(|e| e.len() == 2 && digits(e))
 }
 
 pub fn generate_fields(fields: &Vec<FieldInfo>) -> String {
     let mut result = String::new();
     for field in fields {
-        let source = match field.types.as_str() {
-            "array" => {
-                let types = if field.sub_type.is_empty() {
-                    String::new()
-                } else {
-                    format!("<{}>", field.sub_type)
-                };
-                format!("final List{} {};\n", types, field.name)
+        let source = format!("final {} {};\n", dart_type(field), field.name);
+        result.push_str(&source);
+    }
+    result
+}
+
+/// The declared dart type of a field.
+pub fn dart_type(field: &FieldInfo) -> String {
+    match field.types().as_str() {
+        "array" => {
+            if field.sub_type().is_empty() {
+                String::from("List")
+            } else {
+                format!("List<{}>", field.sub_type())
             }
-            _ => {
-                let types = if field.types == "dynamic" || field.default || field.required {
-                    field.types.clone()
-                } else {
-                    format!("{}?", field.types)
-                };
-                format!("final {} {};\n", types, field.name)
+        }
+        "object" | "enum" => {
+            if is_nullable(field) {
+                format!("{}?", field.sub_type())
+            } else {
+                field.sub_type()
             }
-        };
+        }
+        _ => {
+            if field.ty != Type::Dynamic && is_nullable(field) {
+                format!("{}?", field.types())
+            } else {
+                field.types()
+            }
+        }
+    }
+}
 
-        result.push_str(&source);
+/// Whether the field is declared nullable, arrays default to an empty list.
+fn declared_nullable(field: &FieldInfo) -> bool {
+    field.ty == Type::Dynamic || (!matches!(field.ty, Type::List(..)) && is_nullable(field))
+}
+
+pub fn generate_copy_with(class_name: &str, fields: &[FieldInfo]) -> String {
+    let mut params = String::new();
+    let mut values = String::new();
+    for field in fields {
+        if declared_nullable(field) {
+            params.push_str(&format!(
+                "{} Function()? {},\n",
+                dart_type(field),
+                field.name
+            ));
+            values.push_str(&format!(
+                "{name}: {name} != null ? {name}() : this.{name},\n",
+                name = field.name
+            ));
+        } else {
+            params.push_str(&format!("{}? {},\n", dart_type(field), field.name));
+            values.push_str(&format!(
+                "{name}: {name} ?? this.{name},\n",
+                name = field.name
+            ));
+        }
+    }
+    if fields.is_empty() {
+        return format!(
+            "{} copyWith() {{\nreturn {}();\n}}\n",
+            class_name, class_name
+        );
+    }
+
+    format!(
+        "{class_name} copyWith({{\n{params}}}) {{\nreturn {class_name}(\n{values});\n}}\n",
+        class_name = class_name,
+        params = params,
+        values = values
+    )
+}
+
+/// `==` and `hashCode` comparing and hashing lists element by element.
+pub fn generate_equality(class_name: &str, fields: &[FieldInfo]) -> String {
+    let mut compares = vec![format!("other is {}", class_name)];
+    let mut hashes = Vec::new();
+    for field in fields {
+        if matches!(field.ty, Type::List(..)) {
+            compares.push(format!(
+                "_listEquals(other.{name}, {name})",
+                name = field.name
+            ));
+            hashes.push(format!("_listHash({})", field.name));
+        } else {
+            compares.push(format!("other.{name} == {name}", name = field.name));
+            hashes.push(field.name.clone());
+        }
+    }
+
+    let mut result = format!(
+        "@override\nbool operator ==(Object other) {{\nif (identical(this, other)) return true;\nreturn {};\n}}\n\n",
+        compares.join(" &&\n")
+    );
+    result.push_str(&format!(
+        "@override\nint get hashCode => Object.hashAll([{}]);\n",
+        hashes.join(", ")
+    ));
+    if fields.iter().any(|e| matches!(e.ty, Type::List(..))) {
+        result.push_str(
+            r#"
+static bool _listEquals(List? a, List? b) {
+  if (identical(a, b)) return true;
+  if (a == null || b == null || a.length != b.length) return false;
+  for (var i = 0; i < a.length; i++) {
+    final x = a[i], y = b[i];
+    if (x is List && y is List) {
+      if (!_listEquals(x, y)) return false;
+    } else if (x != y) {
+      return false;
     }
+  }
+  return true;
+}
+
+static int _listHash(List a) =>
+    Object.hashAll(a.map((e) => e is List ? _listHash(e) : e));
+"#,
+        );
+    }
+    // what follows is set apart like after a method with a block body
+    result.push('\n');
     result
 }
 
+pub fn generate_to_string(class_name: &str, fields: &[FieldInfo]) -> String {
+    let values: Vec<String> = fields
+        .iter()
+        .map(|e| format!("{name}: ${name}", name = e.name))
+        .collect();
+    format!(
+        "@override\nString toString() => '{}({})';\n",
+        class_name,
+        values.join(", ")
+    )
+}
+
 pub fn generate_ctor(fields: &Vec<FieldInfo>) -> String {
     let mut result = String::new();
     for field in fields {
-        let source = match &field.types[..] {
+        let source = match &field.types()[..] {
             "array" => format!("this.{} = const [],", field.name),
+            "object" | "enum" => {
+                if matches!(field.ty, Type::Enum(_)) && field.default {
+                    format!(
+                        "this.{} = {}.{},",
+                        field.name,
+                        field.sub_type(),
+                        enum_member(&serde_json::from_str(&field.value).unwrap())
+                    )
+                } else if field.required {
+                    format!("required this.{},", field.name)
+                } else {
+                    format!("this.{},", field.name)
+                }
+            }
             _ => {
                 if field.default {
-                    format!("this.{} = {},", field.name, field.value)
+                    format!("this.{} = {},", field.name, default_literal(field))
                 } else if field.required {
                     format!("required this.{},", field.name)
                 } else {
@@ -212,38 +1090,89 @@ pub fn generate_ctor(fields: &Vec<FieldInfo>) -> String {
 pub fn generate_from_json(fields: &Vec<FieldInfo>) -> String {
     let mut result = String::new();
     for field in fields {
-        let source = match field.types.as_str() {
+        let sub_type = field.sub_type();
+        let source = match field.types().as_str() {
             "array" => {
-                if field.sub_type.is_empty() {
+                if sub_type.is_empty() || sub_type == "dynamic" {
                     format!(
-                        "{name}: json['{name}'] as List? ?? [],\n",
-                        name = field.name
+                        "{name}: json[{key}] as List? ?? [],\n",
+                        name = field.name,
+                        key = wire_key(field)
                     )
                 } else {
-                    let map_expression =
-                        if ["int", "String", "double"].contains(&field.sub_type.as_str()) {
-                            format!("(e) => e as {}", field.sub_type)
-                        } else {
-                            format!("(e) => {}.fromJson(e)", field.sub_type)
-                        };
+                    let element = sub_type.trim_end_matches('?');
+                    let map_expression = if is_converted(element) && sub_type.ends_with('?') {
+                        format!("(e) => e == null ? null : {}", decode(element, "", "e", true))
+                    } else if is_converted(element) {
+                        format!("(e) => {}", decode(element, "", "e", false))
+                    } else if !is_model(&sub_type) {
+                        format!("(e) => e as {}", sub_type)
+                    } else if let Some(sub_type) = sub_type.strip_suffix('?') {
+                        format!("(e) => e == null ? null : {}.fromJson(e)", sub_type)
+                    } else {
+                        format!("(e) => {}.fromJson(e)", sub_type)
+                    };
                     format!(
-                        "{name}: (json['{name}'] as List? ?? []).map({map_expression}).toList(),\n",
+                        "{name}: (json[{key}] as List? ?? []).map({map_expression}).toList(),\n",
                         name = field.name,
+                        key = wire_key(field),
                         map_expression = map_expression
                     )
                 }
             }
+            "enum" if field.default => format!(
+                "{name}: json[{key}] == null ? {sub_type}.{member} : {sub_type}.fromJson(json[{key}]),\n",
+                name = field.name,
+                key = wire_key(field),
+                sub_type = sub_type,
+                member = enum_member(&serde_json::from_str(&field.value).unwrap())
+            ),
+            "object" | "enum" => {
+                if !is_nullable(field) {
+                    format!(
+                        "{name}: {sub_type}.fromJson(json[{key}]),\n",
+                        name = field.name,
+                        key = wire_key(field),
+                        sub_type = sub_type
+                    )
+                } else {
+                    format!(
+                        "{name}: json[{key}] == null ? null : {sub_type}.fromJson(json[{key}]),\n",
+                        name = field.name,
+                        key = wire_key(field),
+                        sub_type = sub_type
+                    )
+                }
+            }
+            types if is_converted(types) => {
+                let value = format!("json[{}]", wire_key(field));
+                if is_nullable(field) {
+                    format!(
+                        "{name}: {value} == null ? null : {decode},\n",
+                        name = field.name,
+                        value = value,
+                        decode = decode(types, &sub_type, &value, true)
+                    )
+                } else {
+                    format!(
+                        "{name}: {decode},\n",
+                        name = field.name,
+                        decode = decode(types, &sub_type, &value, false)
+                    )
+                }
+            }
             _ => {
-                let types = if field.types == "dynamic" || field.required {
+                let types = if field.ty == Type::Dynamic || field.required {
                     String::new()
                 } else if field.default {
-                    format!("as {}? ?? {}", field.types, field.value)
+                    format!("as {}? ?? {}", field.types(), default_literal(field))
                 } else {
-                    format!("as {}?", field.types)
+                    format!("as {}?", field.types())
                 };
                 format!(
-                    "{name}: json['{name}'] {types},\n",
+                    "{name}: json[{key}] {types},\n",
                     name = field.name,
+                    key = wire_key(field),
                     types = types
                 )
             }
@@ -256,17 +1185,414 @@ pub fn generate_from_json(fields: &Vec<FieldInfo>) -> String {
 pub fn generate_to_json(fields: &Vec<FieldInfo>) -> String {
     fields
         .iter()
-        .map(|field| format!("'{name}': {name},\n", name = field.name))
+        .map(|field| {
+            let sub_type = field.sub_type();
+            match field.types().as_str() {
+                "array" if is_converted(sub_type.trim_end_matches('?')) => {
+                    let element = sub_type.trim_end_matches('?');
+                    let value = if sub_type.ends_with('?') { "e?" } else { "e" };
+                    format!(
+                        "{key}: {name}.map((e) => {encode}).toList(),\n",
+                        name = field.name,
+                        key = wire_key(field),
+                        encode = encode(element, "", value)
+                    )
+                }
+                types if is_converted(types) => {
+                    let value = if is_nullable(field) {
+                        format!("{}?", field.name)
+                    } else {
+                        field.name.clone()
+                    };
+                    format!(
+                        "{key}: {encode},\n",
+                        key = wire_key(field),
+                        encode = encode(types, &sub_type, &value)
+                    )
+                }
+                "array" if is_model(&sub_type) && sub_type.ends_with('?') => format!(
+                    "{key}: {name}.map((e) => e?.toJson()).toList(),\n",
+                    name = field.name,
+                    key = wire_key(field)
+                ),
+                "array" if is_model(&sub_type) => format!(
+                    "{key}: {name}.map((e) => e.toJson()).toList(),\n",
+                    name = field.name,
+                    key = wire_key(field)
+                ),
+                "object" | "enum" if !is_nullable(field) => {
+                    format!(
+                        "{key}: {name}.toJson(),\n",
+                        name = field.name,
+                        key = wire_key(field)
+                    )
+                }
+                "object" | "enum" => format!(
+                    "{key}: {name}?.toJson(),\n",
+                    name = field.name,
+                    key = wire_key(field)
+                ),
+                _ => format!("{key}: {name},\n", name = field.name, key = wire_key(field)),
+            }
+        })
         .collect::<String>()
 }
 
-pub fn generate_imports(imports: Vec<String>) -> String {
-    imports
+/// Fields annotated for `json_serializable`, the json code is left to build_runner.
+pub fn generate_annotated_fields(fields: &[FieldInfo], enums: &[EnumInfo]) -> String {
+    let mut result = String::new();
+    for field in fields {
+        let default = annotated_default(field).map(|e| format!("defaultValue: {}", e));
+        result.push_str(&json_key(field, enums, default));
+        result.push_str(&format!("final {} {};\n", dart_type(field), field.name));
+    }
+    result
+}
+
+/// The parameters of a `freezed` factory constructor, braces included.
+pub fn generate_freezed_params(fields: &[FieldInfo], enums: &[EnumInfo]) -> String {
+    if fields.is_empty() {
+        return String::new();
+    }
+    let mut result = String::from("{\n");
+    for field in fields {
+        let default = annotated_default(field);
+        if let Some(value) = &default {
+            result.push_str(&format!("@Default({}) ", value));
+        }
+        result.push_str(&json_key(field, enums, None));
+        if default.is_none() && !declared_nullable(field) {
+            result.push_str("required ");
+        }
+        result.push_str(&format!("{} {},\n", dart_type(field), field.name));
+    }
+    result.push('}');
+    result
+}
+
+/// Functions reading converted types the way the plain models do, where
+/// `json_serializable` would store them differently.
+pub fn generate_converters(fields: &[FieldInfo]) -> String {
+    let mut result = String::new();
+    for field in fields.iter().filter(|e| matches!(e.ty, Type::List(..))) {
+        if ["Duration", "DateTime"].contains(&field.sub_type().trim_end_matches('?')) {
+            println!(
+                "\t  warning: the elements of `{}` are read by json_serializable, not as milliseconds",
+                field.name
+            );
+        }
+    }
+    if fields.iter().any(|e| converter(e) == Some("Duration")) {
+        result.push_str(
+            "\nDuration _durationFromJson(int json) => Duration(milliseconds: json);\n\nint _durationToJson(Duration value) => value.inMilliseconds;\n",
+        );
+    }
+    if fields.iter().any(|e| converter(e) == Some("DateTime")) {
+        result.push_str(
+            "\nDateTime _dateTimeFromJson(int json) => DateTime.fromMillisecondsSinceEpoch(json);\n\nint _dateTimeToJson(DateTime value) => value.millisecondsSinceEpoch;\n",
+        );
+    }
+    result
+}
+
+/// The type needing a converter, durations are milliseconds and date times
+/// with an `int` hint epoch milliseconds.
+fn converter(field: &FieldInfo) -> Option<&'static str> {
+    match (field.types().as_str(), field.sub_type().as_str()) {
+        ("Duration", _) => Some("Duration"),
+        ("DateTime", "int") => Some("DateTime"),
+        _ => None,
+    }
+}
+
+/// `@JsonKey` for a renamed key, an enum falling back to `unknown` or a
+/// converted type, `default` is added as is.
+fn json_key(field: &FieldInfo, enums: &[EnumInfo], default: Option<String>) -> String {
+    let mut args = Vec::new();
+    if field.key != field.name {
+        args.push(format!("name: {}", wire_key(field)));
+    }
+    args.extend(default);
+    let sub_type = field.sub_type();
+    let element = sub_type.trim_end_matches('?');
+    if matches!(field.ty, Type::Enum(_))
+        || matches!(field.ty, Type::List(..)) && enums.iter().any(|e| e.name == element)
+    {
+        args.push(format!("unknownEnumValue: {}.unknown", element));
+    }
+    if let Some(types) = converter(field) {
+        let name = types.to_case(convert_case::Case::Camel);
+        args.push(format!("fromJson: _{}FromJson", name));
+        args.push(format!("toJson: _{}ToJson", name));
+    }
+
+    if args.is_empty() {
+        String::new()
+    } else {
+        format!("@JsonKey({}) ", args.join(", "))
+    }
+}
+
+/// The value of a `d@` field or of an array when json lacks it.
+fn annotated_default(field: &FieldInfo) -> Option<String> {
+    if matches!(field.ty, Type::List(..)) {
+        return Some(String::from("[]"));
+    }
+    if !field.default {
+        return None;
+    }
+    if matches!(field.ty, Type::Enum(_)) {
+        let value: Value = serde_json::from_str(&field.value).ok()?;
+        Some(format!("{}.{}", field.sub_type(), enum_member(&value)))
+    } else {
+        Some(default_literal(field))
+    }
+}
+
+/// The `d@` default of a scalar field as a dart literal, `value` is json.
+fn default_literal(field: &FieldInfo) -> String {
+    serde_json::from_str(&field.value).map_or_else(|_| field.value.clone(), |e| dart_literal(&e))
+}
+
+/// The wire key of a field as a dart string literal.
+fn wire_key(field: &FieldInfo) -> String {
+    dart_literal(&Value::String(field.key.clone()))
+}
+
+pub fn is_model(types: &str) -> bool {
+    let types = types.trim_end_matches('?');
+    !types.is_empty()
+        && !is_converted(types)
+        && !["int", "String", "double", "num", "bool", "dynamic"].contains(&types)
+}
+
+/// Types stored in json as another type, `sub_type` of such a field is the json type.
+fn is_converted(types: &str) -> bool {
+    ["DateTime", "Duration", "Uri"].contains(&types)
+}
+
+/// Dart expression reading the json value `value` stored as `wire`.
+fn decode(types: &str, wire: &str, value: &str, nullable: bool) -> String {
+    match types {
+        "DateTime" if wire == "int" => {
+            format!("DateTime.fromMillisecondsSinceEpoch({} as int)", value)
+        }
+        "DateTime" if nullable => format!("DateTime.tryParse({} as String)", value),
+        "DateTime" => format!("DateTime.parse({} as String)", value),
+        "Duration" => format!("Duration(milliseconds: {} as int)", value),
+        _ if nullable => format!("Uri.tryParse({} as String)", value),
+        _ => format!("Uri.parse({} as String)", value),
+    }
+}
+
+/// Dart expression writing `value` back as `wire`, `value` may end with `?`.
+fn encode(types: &str, wire: &str, value: &str) -> String {
+    match types {
+        "DateTime" if wire == "int" => format!("{}.millisecondsSinceEpoch", value),
+        "DateTime" => format!("{}.toIso8601String()", value),
+        "Duration" => format!("{}.inMilliseconds", value),
+        _ => format!("{}.toString()", value),
+    }
+}
+
+fn is_nullable(field: &FieldInfo) -> bool {
+    field.nullable || !(field.required || field.default)
+}
+
+/// Dart identifier of the enum member for a wire value, `value` is taken by
+/// the field holding the wire value.
+pub fn enum_member(value: &Value) -> String {
+    let name = match value {
+        Value::String(val) => field_name(val),
+        _ => field_name(&value.to_string()),
+    };
+    if name == "value" {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+pub fn dart_literal(value: &Value) -> String {
+    match value {
+        Value::String(val) => format!(
+            "'{}'",
+            val.replace('\\', "\\\\")
+                .replace('\'', "\\'")
+                .replace('$', "\\$")
+                .replace('\n', "\\n")
+        ),
+        _ => value.to_string(),
+    }
+}
+
+/// `annotated` adds the `@JsonValue` json_serializable reads members with.
+pub fn generate_enums(
+    enums: &[EnumInfo],
+    annotated: bool,
+    templates: &Templates,
+) -> Result<String, String> {
+    let mut result = String::new();
+    for item in enums {
+        let mut values: Vec<String> = item
+            .values
+            .iter()
+            .map(|value| {
+                let member = format!("{}({})", enum_member(value), dart_literal(value));
+                if annotated {
+                    format!("@JsonValue({}) {}", dart_literal(value), member)
+                } else {
+                    member
+                }
+            })
+            .collect();
+        if !item
+            .values
+            .iter()
+            .any(|value| enum_member(value) == "unknown")
+        {
+            values.push(String::from("unknown(null)"));
+        }
+
+        let source = templates.render("enum", context! { enum => item, members => values })?;
+        result.push_str(&source);
+    }
+    Ok(result)
+}
+
+pub fn generate_union_from_json(class_name: &str, union: &UnionInfo) -> String {
+    let mut result = String::new();
+    match &union.key {
+        Some(key) => {
+            let key = dart_literal(&Value::String(key.clone()));
+            result.push_str(&format!("switch (json[{}]) {{\n", key));
+            for variant in &union.variants {
+                if let Some(tag) = &variant.tag {
+                    result.push_str(&format!(
+                        "case {}:\nreturn {}.fromJson(json);\n",
+                        dart_literal(tag),
+                        model_name(&variant.info.name)
+                    ));
+                }
+            }
+            match &union.fallback {
+                Some(fallback) => result.push_str(&format!(
+                    "default:\nreturn {}(json);\n}}",
+                    model_name(fallback)
+                )),
+                None => result.push_str(&format!(
+                    "default:\nthrow ArgumentError('Unknown {} type: ${{json[{}]}}');\n}}",
+                    class_name, key
+                )),
+            }
+        }
+        None => {
+            for variant in &union.variants {
+                let keys: Vec<String> = variant
+                    .info
+                    .fields
+                    .iter()
+                    .filter(|e| e.required)
+                    .map(|e| format!("json.containsKey({})", wire_key(e)))
+                    .collect();
+                let variant_name = model_name(&variant.info.name);
+                if keys.is_empty() {
+                    result.push_str(&format!("return {}.fromJson(json);", variant_name));
+                    return result;
+                }
+                result.push_str(&format!(
+                    "if ({}) {{\nreturn {}.fromJson(json);\n}}\n",
+                    keys.join(" && "),
+                    variant_name
+                ));
+            }
+            match &union.fallback {
+                Some(fallback) => {
+                    result.push_str(&format!("return {}(json);", model_name(fallback)))
+                }
+                None => result.push_str(&format!(
+                    "throw ArgumentError('Unknown {}: $json');",
+                    class_name
+                )),
+            }
+        }
+    }
+    result
+}
+
+/// Import `imports` from a model in `dir`. An import is a model name, or a
+/// `dir/name` path below the input path, `dirs` holds the dirs of every model name.
+pub fn generate_imports(
+    dir: &str,
+    imports: &[String],
+    dirs: &HashMap<String, Vec<String>>,
+) -> String {
+    resolve_imports(dir, imports, dirs, model_file)
         .iter()
-        .map(|name| format!("import \"{}.g.dart\";\n", name))
+        .map(|(_, path)| format!("import \"{}\";\n", path))
         .collect::<String>()
 }
 
+/// The imported model names with the path of their file as seen from `dir`,
+/// `file` turns a model name into its file name.
+pub fn resolve_imports(
+    dir: &str,
+    imports: &[String],
+    dirs: &HashMap<String, Vec<String>>,
+    file: impl Fn(&str) -> String,
+) -> Vec<(String, String)> {
+    imports
+        .iter()
+        .map(|import| {
+            let (target, name) = match import.rsplit_once('/') {
+                Some((target, name)) => (target, name),
+                None => (import_dir(dir, import, dirs), import.as_str()),
+            };
+            let path = relative_path(dir, &join_dir(target, &file(name)));
+            (name.to_string(), path)
+        })
+        .collect()
+}
+
+/// A model in the same dir wins over models of the same name elsewhere.
+fn import_dir<'a>(dir: &'a str, name: &str, dirs: &'a HashMap<String, Vec<String>>) -> &'a str {
+    match dirs.get(name).map(|e| e.as_slice()) {
+        Some([only]) => only,
+        Some(found) if !found.is_empty() && !found.iter().any(|e| e == dir) => {
+            println!(
+                "\t  warning: `{}` is in several dirs, use `[]dir/{}` to pick one",
+                name, name
+            );
+            &found[0]
+        }
+        _ => dir,
+    }
+}
+
+/// The path of `file` as seen from `dir`, both relative to the same root.
+fn relative_path(dir: &str, file: &str) -> String {
+    let from: Vec<&str> = dir.split('/').filter(|e| !e.is_empty()).collect();
+    let to: Vec<&str> = file.split('/').filter(|e| !e.is_empty()).collect();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(to.len() - 1);
+
+    let mut parts: Vec<&str> = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+pub fn generate_barrel(files: &[String]) -> String {
+    let mut result = String::from("library;\n\n");
+    for file in files {
+        result.push_str(&format!("export '{}';\n", file));
+    }
+    result
+}
+
 pub(crate) fn generate_ikeys(trans_items: &Vec<TransItem>) -> String {
     let mut result = String::from("library;\n");
 
@@ -282,11 +1608,13 @@ pub(crate) fn generate_ikeys(trans_items: &Vec<TransItem>) -> String {
 fn gen_ikey_class(trans_items: &[TransItem]) -> String {
     let mut result = String::from("/// 国际化文本常量\nclass Ikey {\nIkey._();\n");
     for item in trans_items {
-        let class = item.prefix.to_case(convert_case::Case::Pascal);
+        let class = type_name(&item.prefix);
         result.push_str(
             format!(
                 "///{}\nstatic final {} = Auto{}();\n",
-                item.tips, item.prefix, class
+                item.tips,
+                dart_identifier(&item.prefix),
+                class
             )
             .as_str(),
         );
@@ -304,13 +1632,13 @@ fn gen_trans_class(item: &TransItem) -> String {
         format!(
             "///{}\n class Auto{} {{\n",
             item.tips,
-            item.prefix.to_case(convert_case::Case::Pascal)
+            type_name(&item.prefix)
         )
         .as_str(),
     );
     for (key, _) in item.content.iter() {
         let v_key = format!("{}_{}", item.prefix, key.to_case(convert_case::Case::Snake));
-        let key = format!("k{}", key.to_case(convert_case::Case::Pascal));
+        let key = format!("k{}", type_name(key));
         result.push_str(format!("final {} = '{}';\n", key, v_key).as_str());
     }
 
@@ -333,27 +1661,61 @@ pub fn check_and_create(path: &str) -> bool {
     }
 }
 
-pub fn write_with_format(file_path: &str, content: &str) {
+/// Write a generated file, it is formatted afterwards together with the
+/// other files of the build by `format_files`.
+pub fn write_file(file_path: &str, content: &str) -> bool {
     match fs::write(file_path, content) {
-        Ok(_) => {
-            if let Ok(out_put) = std::process::Command::new("dart")
-                .arg("format")
-                .arg(&file_path)
-                .output()
-            {
-                if out_put.status.success() {
-                    // println!("format file success !");
-                } else {
-                    println!("format file failed!");
-                }
-            } else {
-                println!("Failed to format code");
-            }
+        Ok(_) => true,
+        Err(e) => {
+            println!("write file {} failed: {:?}", file_path, e);
+            false
         }
+    }
+}
+
+/// Run `dart format` once for all files. When it fails, every file named in
+/// its output is formatted again alone to report the error of that file.
+/// Returns the files that failed.
+pub fn format_files(files: &[String]) -> Vec<String> {
+    if files.is_empty() {
+        return Vec::new();
+    }
+
+    let out_put = match std::process::Command::new("dart")
+        .arg("format")
+        .args(files)
+        .output()
+    {
+        Ok(out_put) => out_put,
         Err(e) => {
-            println!("write file failed: {:?}", e);
+            println!("Failed to format code: {}", e);
+            return Vec::new();
         }
+    };
+    if out_put.status.success() {
+        return Vec::new();
     }
+
+    let stderr = String::from_utf8_lossy(&out_put.stderr);
+    let failed: Vec<String> = files
+        .iter()
+        .filter(|e| stderr.contains(e.as_str()))
+        .cloned()
+        .collect();
+    if failed.is_empty() {
+        println!("format files failed:\n{}", stderr.trim_end());
+        return files.to_vec();
+    }
+    for file in &failed {
+        let stderr = std::process::Command::new("dart")
+            .arg("format")
+            .arg(file)
+            .output()
+            .map(|e| String::from_utf8_lossy(&e.stderr).to_string())
+            .unwrap_or_default();
+        println!("format {} failed:\n{}", file, stderr.trim_end());
+    }
+    failed
 }
 
 pub(crate) fn generate_translation(trans_items: &Vec<TransItem>, lang: &str) -> String {
@@ -375,6 +1737,44 @@ pub(crate) fn generate_translation(trans_items: &Vec<TransItem>, lang: &str) ->
     result
 }
 
+/// The `index.dart` the translation files are parts of, exporting the keys
+/// and looking up the messages of a locale.
+pub(crate) fn generate_translation_index(languages: &[String]) -> String {
+    let mut parts = String::new();
+    let mut locales = Vec::new();
+    let mut messages = String::new();
+    for lang in languages {
+        parts.push_str(&format!("part 'i18n_{}.dart';\n", lang));
+        locales.push(format!("'{}'", lang));
+        messages.push_str(&format!("'{}': {}Message,\n", lang, lang));
+    }
+
+    format!(
+        r#"library;
+
+export 'const_key.dart';
+
+{parts}
+/// Every locale with messages, the first one is the fallback.
+const supportedLocales = <String>[{locales}];
+
+final _messages = <String, Map<String, String>>{{
+{messages}}};
+
+/// The messages of `locale` like `zh_CN`, else of its language, else of the
+/// first supported locale.
+Map<String, String> messagesOf(String locale) {{
+  return _messages[locale] ??
+      _messages[locale.split(RegExp('[-_]')).first] ??
+      _messages[supportedLocales.first]!;
+}}
+"#,
+        parts = parts,
+        locales = locales.join(", "),
+        messages = messages
+    )
+}
+
 #[allow(unused)]
 pub(crate) fn translate_from_json_to_csv(
     trans_items: &Vec<TransItem>,
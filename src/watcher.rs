@@ -0,0 +1,78 @@
+use notify_debouncer_full::new_debouncer;
+use notify_debouncer_full::notify::RecursiveMode;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Changes closer together than this are handled as one burst.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Block and call `on_change` with the changed paths after every burst of
+/// changes. A dir is watched recursively, a file through its parent dir so
+/// editors replacing the file are still noticed. A path that can't be
+/// watched is reported and left out.
+pub fn watch(paths: &[String], mut on_change: impl FnMut(&[PathBuf])) {
+    let mut watched = Vec::new();
+    for path in paths {
+        match fs::canonicalize(path) {
+            Ok(path) => watched.push(path),
+            Err(e) => println!("watch {} failed: {}", path, e),
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(DEBOUNCE, None, tx)
+        .unwrap_or_else(|e| panic!("create watcher failed: {}", e));
+    let mut paths = Vec::new();
+    for path in watched {
+        let (dir, mode) = if path.is_dir() {
+            (path.as_path(), RecursiveMode::Recursive)
+        } else {
+            (
+                path.parent().unwrap_or(Path::new("/")),
+                RecursiveMode::NonRecursive,
+            )
+        };
+        if let Err(e) = debouncer.watch(dir, mode) {
+            println!("watch {} failed: {}", dir.display(), e);
+            continue;
+        }
+        println!("watching: {}", path.display());
+        paths.push(path);
+    }
+    if paths.is_empty() {
+        return;
+    }
+    println!("waiting for changes, press ctrl-c to stop");
+
+    for result in rx {
+        match result {
+            Ok(events) => {
+                // reading the sources while building must not start another build
+                let mut changed: Vec<PathBuf> = events
+                    .into_iter()
+                    .filter(|e| !e.kind.is_access() && !e.kind.is_other())
+                    .flat_map(|e| e.event.paths)
+                    .filter(|e| paths.iter().any(|path| e.starts_with(path)) && !e.is_dir())
+                    .collect();
+                changed.sort();
+                changed.dedup();
+                if !changed.is_empty() {
+                    for path in &changed {
+                        println!("changed: {}", path.display());
+                    }
+                    // a build failing on a half written file reports the error
+                    // and keeps the files of the last build, the next change
+                    // builds again
+                    on_change(&changed);
+                }
+            }
+            Err(errors) => {
+                for e in errors {
+                    println!("watch error: {}", e);
+                }
+            }
+        }
+    }
+}
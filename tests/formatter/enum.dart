@@ -0,0 +1,33 @@
+
+////////////////////////////////////////////////////////////
+////           ____             __ 
+////          / __ \____ ______/ /_
+////         / / / / __ `/ ___/ __/
+////        / /_/ / /_/ / /  / /_  
+////       /_____/\__,_/_/   \__/  
+////                             
+////   GENERATED CODE - DO NOT MODIFY BY HAND
+//// 
+////////////////////////////////////////////////////////////
+library;
+
+enum Status {
+  active('active'),
+banned('banned'),
+onHold('on-hold'),
+k2(2),
+unknown(null);
+
+  final dynamic value;
+
+  const Status(this.value);
+
+  static Status fromJson(dynamic json) {
+    return Status.values.firstWhere(
+      (e) => e.value == json,
+      orElse: () => Status.unknown,
+    );
+  }
+
+  dynamic toJson() => value;
+}
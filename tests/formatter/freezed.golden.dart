@@ -0,0 +1,58 @@
+////////////////////////////////////////////////////////////
+////           ____             __
+////          / __ \____ ______/ /_
+////         / / / / __ `/ ___/ __/
+////        / /_/ / /_/ / /  / /_
+////       /_____/\__,_/_/   \__/
+////
+////   GENERATED CODE - DO NOT MODIFY BY HAND
+////
+////////////////////////////////////////////////////////////
+library;
+
+import 'package:freezed_annotation/freezed_annotation.dart';
+import "user_addr.g.dart";
+import "user_items.g.dart";
+
+part 'user.g.freezed.dart';
+part 'user.g.g.dart';
+
+@freezed
+abstract class UserModel with _$UserModel {
+  @JsonSerializable(explicitToJson: true)
+  const factory UserModel({
+    UserAddrModel? addr,
+    @JsonKey(name: 'created_at') DateTime? createdAt,
+    @Default(3) int count,
+    dynamic dynamicThing,
+    @JsonKey(unknownEnumValue: UserStatus.unknown) UserStatus? status,
+    @Default([]) List<UserItemsModel> items,
+    String? name,
+    required int id,
+    @Default([]) List<String> tags,
+  }) = _UserModel;
+
+  factory UserModel.fromJson(Map<String, dynamic> json) =>
+      _$UserModelFromJson(json);
+}
+
+enum UserStatus {
+  @JsonValue('active')
+  active('active'),
+  @JsonValue('banned')
+  banned('banned'),
+  unknown(null);
+
+  final dynamic value;
+
+  const UserStatus(this.value);
+
+  static UserStatus fromJson(dynamic json) {
+    return UserStatus.values.firstWhere(
+      (e) => e.value == json,
+      orElse: () => UserStatus.unknown,
+    );
+  }
+
+  dynamic toJson() => value;
+}
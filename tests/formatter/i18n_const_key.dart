@@ -0,0 +1,18 @@
+library;
+///通用
+ class AutoCommon {
+final kCancel = 'common_cancel';
+final kOk = 'common_ok';
+}
+///Login
+ class AutoLogin {
+final kTitle = 'login_title';
+}
+/// 国际化文本常量
+class Ikey {
+Ikey._();
+///通用
+static final common = AutoCommon();
+///Login
+static final login = AutoLogin();
+}
\ No newline at end of file
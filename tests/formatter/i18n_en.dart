@@ -0,0 +1,8 @@
+part of 'index.dart';
+final enMessage = <String,String>{
+	// 通用
+"common_cancel": "取消",
+"common_ok": "确定",
+	// Login
+"login_title": "Sign in",
+};
\ No newline at end of file
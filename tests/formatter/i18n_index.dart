@@ -0,0 +1,22 @@
+library;
+
+export 'const_key.dart';
+
+part 'i18n_zh.dart';
+part 'i18n_en.dart';
+
+/// Every locale with messages, the first one is the fallback.
+const supportedLocales = <String>['zh', 'en'];
+
+final _messages = <String, Map<String, String>>{
+'zh': zhMessage,
+'en': enMessage,
+};
+
+/// The messages of `locale` like `zh_CN`, else of its language, else of the
+/// first supported locale.
+Map<String, String> messagesOf(String locale) {
+  return _messages[locale] ??
+      _messages[locale.split(RegExp('[-_]')).first] ??
+      _messages[supportedLocales.first]!;
+}
@@ -0,0 +1,164 @@
+////////////////////////////////////////////////////////////
+////           ____             __
+////          / __ \____ ______/ /_
+////         / / / / __ `/ ___/ __/
+////        / /_/ / /_/ / /  / /_
+////       /_____/\__,_/_/   \__/
+////
+////   GENERATED CODE - DO NOT MODIFY BY HAND
+////
+////////////////////////////////////////////////////////////
+library;
+
+import "user_addr.g.dart";
+import "user_items.g.dart";
+
+class UserModel {
+  final UserAddrModel? addr;
+  final DateTime? createdAt;
+  final int count;
+  final dynamic dynamicThing;
+  final UserStatus? status;
+  final List<UserItemsModel> items;
+  final String? name;
+  final int id;
+  final List<String> tags;
+
+  UserModel({
+    this.addr,
+    this.createdAt,
+    this.count = 3,
+    this.dynamicThing,
+    this.status,
+    this.items = const [],
+    this.name,
+    required this.id,
+    this.tags = const [],
+  });
+
+  factory UserModel.fromJson(Map<String, dynamic> json) {
+    return UserModel(
+      addr: json['addr'] == null ? null : UserAddrModel.fromJson(json['addr']),
+      createdAt: json['created_at'] == null
+          ? null
+          : DateTime.tryParse(json['created_at'] as String),
+      count: json['count'] as int? ?? 3,
+      dynamicThing: json['dynamicThing'],
+      status: json['status'] == null
+          ? null
+          : UserStatus.fromJson(json['status']),
+      items: (json['items'] as List? ?? [])
+          .map((e) => UserItemsModel.fromJson(e))
+          .toList(),
+      name: json['name'] as String?,
+      id: json['id'],
+      tags: (json['tags'] as List? ?? []).map((e) => e as String).toList(),
+    );
+  }
+
+  Map<String, dynamic> toJson() {
+    return {
+      'addr': addr?.toJson(),
+      'created_at': createdAt?.toIso8601String(),
+      'count': count,
+      'dynamicThing': dynamicThing,
+      'status': status?.toJson(),
+      'items': items.map((e) => e.toJson()).toList(),
+      'name': name,
+      'id': id,
+      'tags': tags,
+    };
+  }
+
+  UserModel copyWith({
+    UserAddrModel? Function()? addr,
+    DateTime? Function()? createdAt,
+    int? count,
+    dynamic Function()? dynamicThing,
+    UserStatus? Function()? status,
+    List<UserItemsModel>? items,
+    String? Function()? name,
+    int? id,
+    List<String>? tags,
+  }) {
+    return UserModel(
+      addr: addr != null ? addr() : this.addr,
+      createdAt: createdAt != null ? createdAt() : this.createdAt,
+      count: count ?? this.count,
+      dynamicThing: dynamicThing != null ? dynamicThing() : this.dynamicThing,
+      status: status != null ? status() : this.status,
+      items: items ?? this.items,
+      name: name != null ? name() : this.name,
+      id: id ?? this.id,
+      tags: tags ?? this.tags,
+    );
+  }
+
+  @override
+  bool operator ==(Object other) {
+    if (identical(this, other)) return true;
+    return other is UserModel &&
+        other.addr == addr &&
+        other.createdAt == createdAt &&
+        other.count == count &&
+        other.dynamicThing == dynamicThing &&
+        other.status == status &&
+        _listEquals(other.items, items) &&
+        other.name == name &&
+        other.id == id &&
+        _listEquals(other.tags, tags);
+  }
+
+  @override
+  int get hashCode => Object.hashAll([
+        addr,
+        createdAt,
+        count,
+        dynamicThing,
+        status,
+        _listHash(items),
+        name,
+        id,
+        _listHash(tags)
+      ]);
+
+  static bool _listEquals(List? a, List? b) {
+    if (identical(a, b)) return true;
+    if (a == null || b == null || a.length != b.length) return false;
+    for (var i = 0; i < a.length; i++) {
+      final x = a[i], y = b[i];
+      if (x is List && y is List) {
+        if (!_listEquals(x, y)) return false;
+      } else if (x != y) {
+        return false;
+      }
+    }
+    return true;
+  }
+
+  static int _listHash(List a) =>
+      Object.hashAll(a.map((e) => e is List ? _listHash(e) : e));
+
+  @override
+  String toString() =>
+      'UserModel(addr: $addr, createdAt: $createdAt, count: $count, dynamicThing: $dynamicThing, status: $status, items: $items, name: $name, id: $id, tags: $tags)';
+}
+
+enum UserStatus {
+  active('active'),
+  banned('banned'),
+  unknown(null);
+
+  final dynamic value;
+
+  const UserStatus(this.value);
+
+  static UserStatus fromJson(dynamic json) {
+    return UserStatus.values.firstWhere(
+      (e) => e.value == json,
+      orElse: () => UserStatus.unknown,
+    );
+  }
+
+  dynamic toJson() => value;
+}
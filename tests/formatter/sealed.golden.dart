@@ -0,0 +1,91 @@
+////////////////////////////////////////////////////////////
+////           ____             __
+////          / __ \____ ______/ /_
+////         / / / / __ `/ ___/ __/
+////        / /_/ / /_/ / /  / /_
+////       /_____/\__,_/_/   \__/
+////
+////   GENERATED CODE - DO NOT MODIFY BY HAND
+////
+////////////////////////////////////////////////////////////
+library;
+
+sealed class FeedModel {
+  const FeedModel();
+
+  factory FeedModel.fromJson(Map<String, dynamic> json) {
+    switch (json['type']) {
+      case 'post':
+        return FeedPostModel.fromJson(json);
+      case 'video':
+        return FeedVideoModel.fromJson(json);
+      default:
+        return FeedUnknownModel(json);
+    }
+  }
+
+  Map<String, dynamic> toJson();
+}
+
+class FeedPostModel extends FeedModel {
+  final String title;
+  final String? type;
+
+  FeedPostModel({
+    required this.title,
+    this.type,
+  });
+
+  factory FeedPostModel.fromJson(Map<String, dynamic> json) {
+    return FeedPostModel(
+      title: json['title'],
+      type: json['type'] as String?,
+    );
+  }
+
+  @override
+  Map<String, dynamic> toJson() {
+    return {
+      'title': title,
+      'type': type,
+    };
+  }
+}
+
+class FeedVideoModel extends FeedModel {
+  final int? len;
+  final String? type;
+  final String? url;
+
+  FeedVideoModel({
+    this.len,
+    this.type,
+    this.url,
+  });
+
+  factory FeedVideoModel.fromJson(Map<String, dynamic> json) {
+    return FeedVideoModel(
+      len: json['len'] as int?,
+      type: json['type'] as String?,
+      url: json['url'] as String?,
+    );
+  }
+
+  @override
+  Map<String, dynamic> toJson() {
+    return {
+      'len': len,
+      'type': type,
+      'url': url,
+    };
+  }
+}
+
+class FeedUnknownModel extends FeedModel {
+  final Map<String, dynamic> json;
+
+  FeedUnknownModel(this.json);
+
+  @override
+  Map<String, dynamic> toJson() => json;
+}
@@ -0,0 +1,98 @@
+////////////////////////////////////////////////////////////
+////           ____             __
+////          / __ \____ ______/ /_
+////         / / / / __ `/ ___/ __/
+////        / /_/ / /_/ / /  / /_
+////       /_____/\__,_/_/   \__/
+////
+////   GENERATED CODE - DO NOT MODIFY BY HAND
+////
+////////////////////////////////////////////////////////////
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelModel {
+    pub level: LevelLevel,
+    pub mixed: Option<LevelMixed>,
+    pub state: Option<LevelState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelLevel {
+    K1,
+    K2,
+    K3,
+    Unknown,
+}
+
+impl Serialize for LevelLevel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match self {
+            LevelLevel::K1 => serde_json::json!(1),
+            LevelLevel::K2 => serde_json::json!(2),
+            LevelLevel::K3 => serde_json::json!(3),
+            LevelLevel::Unknown => serde_json::Value::Null,
+        };
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LevelLevel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let members = [
+            (serde_json::json!(1), LevelLevel::K1),
+            (serde_json::json!(2), LevelLevel::K2),
+            (serde_json::json!(3), LevelLevel::K3),
+        ];
+        let found = members.into_iter().find(|e| e.0 == value);
+        Ok(found.map_or(LevelLevel::Unknown, |e| e.1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelMixed {
+    On,
+    K2,
+    K15,
+    True,
+    Unknown,
+}
+
+impl Serialize for LevelMixed {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match self {
+            LevelMixed::On => serde_json::json!("on"),
+            LevelMixed::K2 => serde_json::json!(2),
+            LevelMixed::K15 => serde_json::json!(1.5),
+            LevelMixed::True => serde_json::json!(true),
+            LevelMixed::Unknown => serde_json::Value::Null,
+        };
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LevelMixed {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let members = [
+            (serde_json::json!("on"), LevelMixed::On),
+            (serde_json::json!(2), LevelMixed::K2),
+            (serde_json::json!(1.5), LevelMixed::K15),
+            (serde_json::json!(true), LevelMixed::True),
+        ];
+        let found = members.into_iter().find(|e| e.0 == value);
+        Ok(found.map_or(LevelMixed::Unknown, |e| e.1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelState {
+    #[serde(rename = "a")]
+    A,
+    #[serde(rename = "b")]
+    B,
+    #[serde(other)]
+    Unknown,
+}
@@ -0,0 +1,62 @@
+////////////////////////////////////////////////////////////
+////           ____             __
+////          / __ \____ ______/ /_
+////         / / / / __ `/ ___/ __/
+////        / /_/ / /_/ / /  / /_
+////       /_____/\__,_/_/   \__/
+////
+////   GENERATED CODE - DO NOT MODIFY BY HAND
+////
+////////////////////////////////////////////////////////////
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderModel {
+    pub addr: Option<OrderAddrModel>,
+    #[serde(default = "default_order_model_count")]
+    pub count: i64,
+    #[serde(default)]
+    pub extra: serde_json::Value,
+    #[serde(default = "default_order_model_label")]
+    pub label: String,
+    #[serde(default = "default_order_model_price")]
+    pub price: f64,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default = "default_order_model_status")]
+    pub status: OrderStatus,
+    pub id: i64,
+}
+
+fn default_order_model_count() -> i64 {
+    3
+}
+
+fn default_order_model_label() -> String {
+    String::from("a\"b")
+}
+
+fn default_order_model_price() -> f64 {
+    2.0
+}
+
+fn default_order_model_status() -> OrderStatus {
+    OrderStatus::On
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    #[serde(rename = "on")]
+    On,
+    #[serde(rename = "off")]
+    Off,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderAddrModel {
+    pub city: Option<String>,
+    pub zip: Option<i64>,
+}